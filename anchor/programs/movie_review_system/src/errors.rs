@@ -23,4 +23,94 @@ pub enum MovieReviewSystemError {
     InvalidMintAuthority,
     #[msg("Cooling period not yet passed for withdrawal")]
     CoolingPeriodNotPassed,
-}
\ No newline at end of file
+    #[msg("Only the program upgrade authority can initialize the config")]
+    InvalidConfigInitializer,
+    #[msg("Only the admin can perform this action")]
+    UnauthorizedAdmin,
+    #[msg("Signer is not the pending admin")]
+    InvalidPendingAdmin,
+    #[msg("Signer does not hold the role required for this instruction")]
+    UnauthorizedRole,
+    #[msg("Cooldown must not be negative")]
+    InvalidCooldown,
+    #[msg("Reward amount overflows for the mint's decimals")]
+    RewardAmountOverflow,
+    #[msg("Movie title, director or hero is too long")]
+    MovieFieldTooLong,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Vesting schedule needs 0 <= cliff <= duration and a positive duration")]
+    InvalidVestingSchedule,
+    #[msg("Withdraw amount must be greater than zero")]
+    InvalidWithdrawAmount,
+    #[msg("Withdraw amount exceeds the vested balance")]
+    InsufficientVestedBalance,
+    #[msg("Signer is not the vault's withdraw authority")]
+    UnauthorizedWithdrawAuthority,
+    #[msg("Vault still holds unvested rewards; pass forfeit_unvested to give them up")]
+    UnvestedBalanceRemaining,
+    #[msg("Clear the vault's withdraw authority before closing it")]
+    WithdrawAuthorityStillSet,
+    #[msg("Emission epoch duration must be positive")]
+    InvalidEpochDuration,
+    #[msg("Mint does not match the reward treasury mint")]
+    InvalidRewardMint,
+    #[msg("Epoch pool mode needs the current epoch and participation accounts")]
+    MissingEpochAccounts,
+    #[msg("Epoch account does not match the review's epoch")]
+    EpochMismatch,
+    #[msg("Epoch has not ended yet")]
+    EpochNotEnded,
+    #[msg("Epoch is already finalized")]
+    EpochAlreadyFinalized,
+    #[msg("Epoch is not finalized yet")]
+    EpochNotFinalized,
+    #[msg("Reward mint metadata is too long")]
+    RewardMetadataTooLong,
+    #[msg("This program no longer holds the freeze authority")]
+    InvalidFreezeAuthority,
+    #[msg("Reviewers cannot vote on their own review")]
+    SelfVote,
+    #[msg("Reply is too long")]
+    ReplyTooLong,
+    #[msg("Reply is nested too deeply")]
+    ReplyTooDeep,
+    #[msg("Parent reply belongs to another review")]
+    InvalidParentReply,
+    #[msg("Only the author can change this reply")]
+    UnauthorizedReplyUpdate,
+    #[msg("Review is already hidden")]
+    ReviewAlreadyHidden,
+    #[msg("Review is not hidden")]
+    ReviewNotHidden,
+    #[msg("Aspect ratings must be between 1 and 10")]
+    InvalidAspectRating,
+    #[msg("Rating must be at most 1000 and a multiple of the configured step")]
+    InvalidRatingBps,
+    #[msg("Rating step must be a non-zero divisor of 1000")]
+    InvalidRatingStep,
+    #[msg("Edit intervals and lock windows cannot be negative")]
+    InvalidEditRules,
+    #[msg("Review was edited too recently")]
+    EditTooSoon,
+    #[msg("Review has reached its edit limit")]
+    EditLimitReached,
+    #[msg("Review rating is locked")]
+    RatingLocked,
+    #[msg("Unknown content warning bits")]
+    InvalidContentWarnings,
+    #[msg("Epoch is finalized or has ended; wait for the next epoch")]
+    EpochClosed,
+    #[msg("Account is not in the legacy layout")]
+    AlreadyMigrated,
+    #[msg("Clawback needs the vault token account and the treasury")]
+    MissingClawbackAccounts,
+    #[msg("Review is already flagged by this user")]
+    AlreadyFlagged,
+    #[msg("Movie does not match the legacy movie")]
+    LegacyMovieMismatch,
+    #[msg("Pass the reward receipts being claimed as remaining accounts")]
+    MissingClaimReceipts,
+    #[msg("Release year is part of the movie address and cannot change")]
+    ReleaseYearLocked,
+}
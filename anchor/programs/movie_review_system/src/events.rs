@@ -0,0 +1,361 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AspectRatings, ReviewEditRules, RewardMode, Role, VestingSchedule};
+
+/// Emits through `emit_cpi!` when the `event-cpi` feature is enabled and `emit!` otherwise.
+/// The accounts struct behind `$ctx` must carry `#[cfg_attr(feature = "event-cpi", event_cpi)]`.
+macro_rules! emit_event {
+    ($ctx:ident, $event:expr) => {{
+        #[cfg(feature = "event-cpi")]
+        {
+            let ctx = &$ctx;
+            anchor_lang::prelude::emit_cpi!($event);
+        }
+        #[cfg(not(feature = "event-cpi"))]
+        {
+            anchor_lang::prelude::emit!($event);
+        }
+    }};
+}
+pub(crate) use emit_event;
+
+#[event]
+pub struct ConfigInitialized {
+    pub admin: Pubkey,
+    pub initialized_by: Pubkey,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    /// `None` when a pending handover was cancelled.
+    pub pending_admin: Option<Pubkey>,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub member: Pubkey,
+    pub role: Role,
+    pub granted_by: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub member: Pubkey,
+    pub role: Role,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct RewardConfigUpdated {
+    pub reward_per_review: u64,
+    pub cooldown_seconds: i64,
+    pub vesting: VestingSchedule,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct RewardModeUpdated {
+    pub reward_mode: RewardMode,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct RatingStepUpdated {
+    pub rating_step_bps: u16,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct ReviewEditRulesUpdated {
+    pub edit_rules: ReviewEditRules,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct TreasuryInitialized {
+    pub mint: Pubkey,
+    pub treasury: Pubkey,
+    pub lifetime_cap: u64,
+    pub epoch_cap: u64,
+    pub epoch_duration_seconds: i64,
+}
+
+#[event]
+pub struct EmissionCapsUpdated {
+    pub lifetime_cap: u64,
+    pub epoch_cap: u64,
+    pub epoch_duration_seconds: i64,
+    /// Current epoch after the update; a new duration starts a fresh one.
+    pub epoch_index: u64,
+}
+
+#[event]
+pub struct MovieCreated {
+    pub movie: Pubkey,
+    pub title: String,
+    pub release_year: u16,
+    pub edition: u8,
+    pub created_by: Pubkey,
+}
+
+#[event]
+pub struct MovieMigrated {
+    pub legacy_movie: Pubkey,
+    pub movie: Pubkey,
+    pub edition: u8,
+    pub migrated_by: Pubkey,
+}
+
+#[event]
+pub struct MovieUpdated {
+    pub movie: Pubkey,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct ReviewCreated {
+    pub movie: Pubkey,
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub rating: u8,
+    pub rating_bps: u16,
+    pub reward_eligible: bool,
+    pub spoiler: bool,
+    pub content_warnings: u16,
+    pub aspect_ratings: Option<AspectRatings>,
+}
+
+#[event]
+pub struct ReviewMigrated {
+    pub legacy_review: Pubkey,
+    pub movie: Pubkey,
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub rating_bps: u16,
+}
+
+#[event]
+pub struct ReviewUpdated {
+    pub movie: Pubkey,
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub old_rating: u8,
+    pub new_rating: u8,
+    pub old_rating_bps: u16,
+    pub new_rating_bps: u16,
+    pub revision: u32,
+    pub spoiler: bool,
+    pub content_warnings: u16,
+}
+
+#[event]
+pub struct ReviewDeleted {
+    pub movie: Pubkey,
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub rating: u8,
+    pub rating_bps: u16,
+}
+
+#[event]
+pub struct AspectRatingsUpdated {
+    pub movie: Pubkey,
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub aspect_ratings: Option<AspectRatings>,
+}
+
+#[event]
+pub struct ReviewFlagged {
+    pub review: Pubkey,
+    pub flagger: Pubkey,
+    pub reason_code: u8,
+    pub flag_count: u32,
+}
+
+#[event]
+pub struct ReviewHidden {
+    pub movie: Pubkey,
+    pub review: Pubkey,
+    pub moderator: Pubkey,
+}
+
+#[event]
+pub struct ReviewRestored {
+    pub movie: Pubkey,
+    pub review: Pubkey,
+    pub moderator: Pubkey,
+}
+
+#[event]
+pub struct SpoilerForced {
+    pub review: Pubkey,
+    pub moderator: Pubkey,
+    pub forced: bool,
+}
+
+#[event]
+pub struct ReplyCreated {
+    pub review: Pubkey,
+    pub review_generation: u32,
+    pub reply: Pubkey,
+    pub author: Pubkey,
+    pub parent_reply: Option<Pubkey>,
+    pub depth: u8,
+}
+
+#[event]
+pub struct ReplyUpdated {
+    pub review: Pubkey,
+    pub reply: Pubkey,
+    pub author: Pubkey,
+}
+
+#[event]
+pub struct ReplyDeleted {
+    pub review: Pubkey,
+    pub reply: Pubkey,
+    pub author: Pubkey,
+}
+
+#[event]
+pub struct ReviewVoted {
+    pub review: Pubkey,
+    pub voter: Pubkey,
+    pub helpful: bool,
+    pub helpful_count: u32,
+    pub unhelpful_count: u32,
+}
+
+#[event]
+pub struct ReviewVoteClosed {
+    pub review: Pubkey,
+    pub voter: Pubkey,
+    pub helpful: bool,
+}
+
+#[event]
+pub struct RewardAccrued {
+    pub user: Pubkey,
+    pub review: Pubkey,
+    pub amount: u64,
+    pub cliff_at: i64,
+    pub fully_vested_at: i64,
+    /// Unreleased balance after this reward.
+    pub pending: u64,
+}
+
+#[event]
+pub struct RewardVested {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub withdrawable_amount: u64,
+}
+
+#[event]
+pub struct TokensWithdrawn {
+    pub user: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardClawedBack {
+    pub user: Pubkey,
+    pub review: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WithdrawAuthorityChanged {
+    pub user: Pubkey,
+    pub withdraw_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct VaultClosed {
+    pub user: Pubkey,
+    pub withdrawn: u64,
+    pub forfeited: u64,
+}
+
+#[event]
+pub struct UserVaultMigrated {
+    pub user: Pubkey,
+    pub pending: u64,
+    pub withdrawable_amount: u64,
+}
+
+#[event]
+pub struct RewardBudgetExhausted {
+    pub user: Pubkey,
+    pub review: Pubkey,
+    pub requested: u64,
+    pub lifetime_emitted: u64,
+    pub epoch_emitted: u64,
+    pub treasury_balance: u64,
+}
+
+#[event]
+pub struct RewardMintInitialized {
+    pub mint: Pubkey,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct MintAuthorityChanged {
+    pub mint: Pubkey,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct FreezeAuthorityChanged {
+    pub mint: Pubkey,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct TreasuryFunded {
+    pub amount: u64,
+    pub treasury_balance: u64,
+}
+
+#[event]
+pub struct EpochPointEarned {
+    pub user: Pubkey,
+    pub review: Pubkey,
+    pub epoch_index: u64,
+    pub total_points: u64,
+}
+
+#[event]
+pub struct EpochFinalized {
+    pub epoch_index: u64,
+    pub pool: u64,
+    pub total_points: u64,
+}
+
+#[event]
+pub struct EpochPointForfeited {
+    pub user: Pubkey,
+    pub review: Pubkey,
+    pub epoch_index: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EpochRewardsClaimed {
+    pub user: Pubkey,
+    pub epoch_index: u64,
+    pub points: u64,
+    pub amount: u64,
+    pub cliff_at: i64,
+    pub fully_vested_at: i64,
+}
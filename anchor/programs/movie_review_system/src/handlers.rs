@@ -1,30 +1,556 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{MintTo, mint_to, transfer_checked, TransferChecked};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_2022::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_interface::{
+    CloseAccount, MintTo, SetAuthority, TokenMetadataInitialize, close_account, mint_to, set_authority,
+    token_metadata_initialize, transfer_checked,
+    TransferChecked,
+    spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_token_metadata_interface::state::TokenMetadata,
+};
 
 use crate::{
-    ADMIN_PUBKEY, CreateMovie, CreateReview, DeleteMovieReview, UpdateReview, WithdrawTokens, errors::MovieReviewSystemError
+    ANCHOR_DISCRIMINATOR_SIZE, AcceptAdmin, ClaimEpochRewards, CloseVault, CreateMovie, CreateReview, DeleteMovieReview, FinalizeEpoch, FundTreasury, GetVaultSummary, GrantRole, MigrateMovie, MigrateReview, MigrateUserVault, InitializeConfig, InitializeRewardMint, InitializeTreasury, ManageMintAuthority, ManageWithdrawAuthority, ProposeAdmin, RevokeRole, UpdateEmissionCaps, UpdateMovie, UpdateReview, UpdateRewardConfig, FlagReview, ModerateReview, CreateReply, UpdateReply, DeleteReply, VoteReview, CloseVote, WithdrawTokens, WithdrawTokensV2, errors::MovieReviewSystemError, events::*, state::{AspectRatings, ContentWarnings, ReviewHistory, RewardReceipt, LegacyMovieAccount, LegacyMovieReview, LegacyUserVault, UserVault, ReviewEditRules, ReviewLabels, MovieAccount, MovieReview, ReviewVisibility, ReviewReply, ProgramConfig, RewardMode, Role, VaultSummary, VestingSchedule}
 };
 
+pub fn initialize_config_handler(_ctx: Context<InitializeConfig>, admin: Pubkey) -> Result<()> {
+    let config = &mut _ctx.accounts.config;
+
+    config.bump = _ctx.bumps.config;
+    config.admin = admin;
+    config.pending_admin = None;
+    config.reward_per_review = ProgramConfig::DEFAULT_REWARD_PER_REVIEW;
+    config.cooldown_seconds = ProgramConfig::DEFAULT_COOLDOWN_SECONDS;
+    config.vesting = VestingSchedule::Cliff;
+    config.reward_mode = RewardMode::Flat;
+    config.reward_mint = None;
+    config.rating_step_bps = ProgramConfig::DEFAULT_RATING_STEP_BPS;
+    config.edit_rules = ReviewEditRules::default();
+
+    emit_event!(_ctx, ConfigInitialized {
+        admin,
+        initialized_by: _ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
+
+pub fn update_reward_config_handler(
+    _ctx: Context<UpdateRewardConfig>,
+    reward_per_review: u64,
+    cooldown_seconds: i64,
+    vesting: VestingSchedule,
+) -> Result<()> {
+    if cooldown_seconds < 0 {
+        return Err(MovieReviewSystemError::InvalidCooldown.into());
+    }
+    if !vesting.is_valid() {
+        return Err(MovieReviewSystemError::InvalidVestingSchedule.into());
+    }
+
+    let config = &mut _ctx.accounts.config;
+
+    config.reward_per_review = reward_per_review;
+    config.cooldown_seconds = cooldown_seconds;
+    config.vesting = vesting;
+
+    emit_event!(_ctx, RewardConfigUpdated {
+        reward_per_review,
+        cooldown_seconds,
+        vesting,
+        updated_by: _ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
+pub const MAX_REWARD_NAME_LEN: usize = 32;
+pub const MAX_REWARD_SYMBOL_LEN: usize = 10;
+pub const MAX_REWARD_URI_LEN: usize = 200;
+
+pub fn initialize_reward_mint_handler(
+    _ctx: Context<InitializeRewardMint>,
+    decimals: u8,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    if name.len() > MAX_REWARD_NAME_LEN || symbol.len() > MAX_REWARD_SYMBOL_LEN || uri.len() > MAX_REWARD_URI_LEN {
+        return Err(MovieReviewSystemError::RewardMetadataTooLong.into());
+    }
+
+    let mint_key = _ctx.accounts.reward_mint.key();
+    let mint_auth_key = _ctx.accounts.mint_auth.key();
+
+    // Token-2022 reallocs the mint itself when writing metadata, so top up rent first.
+    let metadata = TokenMetadata {
+        update_authority: OptionalNonZeroPubkey::try_from(Some(mint_auth_key))?,
+        mint: mint_key,
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    };
+    let mint_info = _ctx.accounts.reward_mint.to_account_info();
+    let required_len = mint_info.data_len() + metadata.tlv_size_of()?;
+    let shortfall = Rent::get()?.minimum_balance(required_len).saturating_sub(mint_info.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                _ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: _ctx.accounts.admin.to_account_info(),
+                    to: mint_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    let mint_authority_seeds: &[&[u8]] = &[b"mint_auth", &[_ctx.bumps.mint_auth]];
+
+    token_metadata_initialize(
+        CpiContext::new_with_signer(
+            _ctx.accounts.token_program.to_account_info(),
+            TokenMetadataInitialize {
+                program_id: _ctx.accounts.token_program.to_account_info(),
+                metadata: mint_info.clone(),
+                update_authority: _ctx.accounts.mint_auth.to_account_info(),
+                mint_authority: _ctx.accounts.mint_auth.to_account_info(),
+                mint: mint_info,
+            },
+            &[mint_authority_seeds],
+        ),
+        name,
+        symbol,
+        uri,
+    )?;
+
+    _ctx.accounts.config.reward_mint = Some(mint_key);
+
+    emit_event!(_ctx, RewardMintInitialized {
+        mint: mint_key,
+        decimals,
+    });
+
+    Ok(())
+}
+
+pub fn set_rating_step_handler(_ctx: Context<UpdateRewardConfig>, rating_step_bps: u16) -> Result<()> {
+    if !ProgramConfig::is_valid_rating_step(rating_step_bps) {
+        return Err(MovieReviewSystemError::InvalidRatingStep.into());
+    }
+
+    _ctx.accounts.config.rating_step_bps = rating_step_bps;
+
+    emit_event!(_ctx, RatingStepUpdated {
+        rating_step_bps,
+        updated_by: _ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
+pub fn set_review_edit_rules_handler(_ctx: Context<UpdateRewardConfig>, edit_rules: ReviewEditRules) -> Result<()> {
+    if !edit_rules.is_valid() {
+        return Err(MovieReviewSystemError::InvalidEditRules.into());
+    }
+
+    _ctx.accounts.config.edit_rules = edit_rules;
+
+    emit_event!(_ctx, ReviewEditRulesUpdated {
+        edit_rules,
+        updated_by: _ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
+pub fn initialize_treasury_handler(
+    _ctx: Context<InitializeTreasury>,
+    lifetime_cap: u64,
+    epoch_cap: u64,
+    epoch_duration_seconds: i64,
+) -> Result<()> {
+    if epoch_duration_seconds <= 0 {
+        return Err(MovieReviewSystemError::InvalidEpochDuration.into());
+    }
+
+    let clock = Clock::get()?;
+    let emission_state = &mut _ctx.accounts.emission_state;
+
+    emission_state.bump = _ctx.bumps.emission_state;
+    emission_state.treasury_bump = _ctx.bumps.treasury;
+    emission_state.mint = _ctx.accounts.ast_mint.key();
+    emission_state.treasury = _ctx.accounts.treasury.key();
+    emission_state.lifetime_cap = lifetime_cap;
+    emission_state.lifetime_emitted = 0;
+    emission_state.epoch_cap = epoch_cap;
+    emission_state.epoch_duration_seconds = epoch_duration_seconds;
+    emission_state.epoch_started_at = clock.unix_timestamp;
+    emission_state.epoch_emitted = 0;
+    emission_state.epoch_index = 0;
+    emission_state.reserved = 0;
+
+    emit_event!(_ctx, TreasuryInitialized {
+        mint: _ctx.accounts.emission_state.mint,
+        treasury: _ctx.accounts.emission_state.treasury,
+        lifetime_cap,
+        epoch_cap,
+        epoch_duration_seconds,
+    });
+
+    Ok(())
+}
+
+pub fn update_emission_caps_handler(
+    _ctx: Context<UpdateEmissionCaps>,
+    lifetime_cap: u64,
+    epoch_cap: u64,
+    epoch_duration_seconds: i64,
+) -> Result<()> {
+    if epoch_duration_seconds <= 0 {
+        return Err(MovieReviewSystemError::InvalidEpochDuration.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let emission_state = &mut _ctx.accounts.emission_state;
+
+    // Close out elapsed epochs under the old duration, so indices already handed out keep
+    // their time window. A new duration starts a fresh epoch now, leaving the current one
+    // (and any `Epoch` account for it) to end as scheduled.
+    emission_state.roll_epoch(now);
+    if epoch_duration_seconds != emission_state.epoch_duration_seconds {
+        emission_state.epoch_index += 1;
+        emission_state.epoch_started_at = now;
+        emission_state.epoch_emitted = 0;
+    }
+
+    emission_state.lifetime_cap = lifetime_cap;
+    emission_state.epoch_cap = epoch_cap;
+    emission_state.epoch_duration_seconds = epoch_duration_seconds;
+
+    emit_event!(_ctx, EmissionCapsUpdated {
+        lifetime_cap,
+        epoch_cap,
+        epoch_duration_seconds,
+        epoch_index: _ctx.accounts.emission_state.epoch_index,
+    });
+
+    Ok(())
+}
+
+pub fn fund_treasury_handler(_ctx: Context<FundTreasury>, amount: u64) -> Result<()> {
+    let mint_authority_seeds: &[&[u8]] = &[b"mint_auth", &[_ctx.bumps.mint_auth]];
+
+    mint_to(
+        CpiContext::new_with_signer(
+            _ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: _ctx.accounts.ast_mint.to_account_info(),
+                to: _ctx.accounts.treasury.to_account_info(),
+                authority: _ctx.accounts.mint_auth.to_account_info(),
+            },
+            &[mint_authority_seeds],
+        ),
+        amount,
+    )?;
+
+    _ctx.accounts.treasury.reload()?;
+    emit_event!(_ctx, TreasuryFunded {
+        amount,
+        treasury_balance: _ctx.accounts.treasury.amount,
+    });
+
+    Ok(())
+}
+
+pub fn set_mint_authority_handler(_ctx: Context<ManageMintAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+    let mint_auth_key = _ctx.accounts.mint_auth.key();
+    if _ctx.accounts.ast_mint.mint_authority != COption::Some(mint_auth_key) {
+        return Err(MovieReviewSystemError::InvalidMintAuthority.into());
+    }
+
+    rotate_mint_authority(&_ctx, AuthorityType::MintTokens, new_authority)?;
+
+    emit_event!(_ctx, MintAuthorityChanged {
+        mint: _ctx.accounts.ast_mint.key(),
+        new_authority,
+    });
+
+    Ok(())
+}
+
+pub fn set_freeze_authority_handler(_ctx: Context<ManageMintAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+    let mint_auth_key = _ctx.accounts.mint_auth.key();
+    if _ctx.accounts.ast_mint.freeze_authority != COption::Some(mint_auth_key) {
+        return Err(MovieReviewSystemError::InvalidFreezeAuthority.into());
+    }
+
+    rotate_mint_authority(&_ctx, AuthorityType::FreezeAccount, new_authority)?;
+
+    emit_event!(_ctx, FreezeAuthorityChanged {
+        mint: _ctx.accounts.ast_mint.key(),
+        new_authority,
+    });
+
+    Ok(())
+}
+
+fn rotate_mint_authority(
+    _ctx: &Context<ManageMintAuthority>,
+    authority_type: AuthorityType,
+    new_authority: Option<Pubkey>,
+) -> Result<()> {
+    let mint_authority_seeds: &[&[u8]] = &[b"mint_auth", &[_ctx.bumps.mint_auth]];
+
+    set_authority(
+        CpiContext::new_with_signer(
+            _ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: _ctx.accounts.mint_auth.to_account_info(),
+                account_or_mint: _ctx.accounts.ast_mint.to_account_info(),
+            },
+            &[mint_authority_seeds],
+        ),
+        authority_type,
+        new_authority,
+    )
+}
+
+pub fn propose_admin_handler(_ctx: Context<ProposeAdmin>, new_admin: Option<Pubkey>) -> Result<()> {
+    // Passing `None` cancels a pending handover.
+    _ctx.accounts.config.pending_admin = new_admin;
+
+    emit_event!(_ctx, AdminProposed {
+        admin: _ctx.accounts.admin.key(),
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
+
+pub fn accept_admin_handler(_ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut _ctx.accounts.config;
+    let previous_admin = config.admin;
+
+    config.admin = _ctx.accounts.new_admin.key();
+    config.pending_admin = None;
+
+    emit_event!(_ctx, AdminAccepted {
+        previous_admin,
+        admin: _ctx.accounts.new_admin.key(),
+    });
+
+    Ok(())
+}
+
+pub fn grant_role_handler(_ctx: Context<GrantRole>, member: Pubkey, role: Role) -> Result<()> {
+    let role_grant = &mut _ctx.accounts.role_grant;
+
+    role_grant.bump = _ctx.bumps.role_grant;
+    role_grant.member = member;
+    role_grant.role = role;
+    role_grant.granted_by = _ctx.accounts.admin.key();
+
+    emit_event!(_ctx, RoleGranted {
+        member,
+        role,
+        granted_by: _ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
+pub fn revoke_role_handler(_ctx: Context<RevokeRole>) -> Result<()> {
+    msg!(
+        "Revoking role grant account: {}",
+        _ctx.accounts.role_grant.key()
+    );
+
+    emit_event!(_ctx, RoleRevoked {
+        member: _ctx.accounts.role_grant.member,
+        role: _ctx.accounts.role_grant.role,
+        revoked_by: _ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
 pub fn create_movie_handler(
     _ctx: Context<CreateMovie>,
     movie: String,
     director: String,
     hero: String,
     release_year: u16,
+    edition: u8,
 ) -> Result<()> {
+    if movie.len() > MovieAccount::MAX_FIELD_LEN
+        || director.len() > MovieAccount::MAX_FIELD_LEN
+        || hero.len() > MovieAccount::MAX_FIELD_LEN
+    {
+        return Err(MovieReviewSystemError::MovieFieldTooLong.into());
+    }
+
     let movie_account = &mut _ctx.accounts.movie_account;
-    let _user = &_ctx.accounts.user;
 
-    if _user.key() != Pubkey::from_str_const(ADMIN_PUBKEY) {
-        return Err(MovieReviewSystemError::InvalidUserToCreateMovie.into());
+    movie_account.movie = movie;
+    movie_account.director = director;
+    movie_account.hero = hero;
+    movie_account.release_year = release_year;
+    movie_account.edition = edition;
+    movie_account.seed_hash = MovieAccount::seed_hash(&movie_account.movie, release_year, edition);
+    movie_account.bump = _ctx.bumps.movie_account;
+
+    let movie_stats = &mut _ctx.accounts.movie_stats;
+    movie_stats.bump = _ctx.bumps.movie_stats;
+    movie_stats.movie = movie_account.key();
+
+    let event = MovieCreated {
+        movie: movie_account.key(),
+        title: movie_account.movie.clone(),
+        release_year,
+        edition,
+        created_by: _ctx.accounts.user.key(),
+    };
+    emit_event!(_ctx, event);
+
+    Ok(())
+}
+
+pub fn update_movie_handler(
+    _ctx: Context<UpdateMovie>,
+    director: String,
+    hero: String,
+    release_year: u16,
+) -> Result<()> {
+    if director.len() > MovieAccount::MAX_FIELD_LEN || hero.len() > MovieAccount::MAX_FIELD_LEN {
+        return Err(MovieReviewSystemError::MovieFieldTooLong.into());
+    }
+    // The release year is hashed into the PDA, so changing it would strand the account
+    // at an address no client derives anymore.
+    if release_year != _ctx.accounts.movie_account.release_year {
+        return Err(MovieReviewSystemError::ReleaseYearLocked.into());
     }
 
-    movie_account.movie = movie;
+    // The account was already resized to fit the new strings by the `realloc` constraint.
+    let movie_account = &mut _ctx.accounts.movie_account;
+
     movie_account.director = director;
     movie_account.hero = hero;
+
+    emit_event!(_ctx, MovieUpdated {
+        movie: _ctx.accounts.movie_account.key(),
+        updated_by: _ctx.accounts.user.key(),
+    });
+
+    Ok(())
+}
+
+pub fn migrate_movie_handler(
+    _ctx: Context<MigrateMovie>,
+    movie: String,
+    release_year: u16,
+    edition: u8,
+) -> Result<()> {
+    let legacy = LegacyMovieAccount::read(&_ctx.accounts.legacy_movie)?;
+    if legacy.movie != movie || legacy.release_year != release_year {
+        return Err(MovieReviewSystemError::LegacyMovieMismatch.into());
+    }
+
+    let movie_account = &mut _ctx.accounts.movie_account;
     movie_account.release_year = release_year;
     movie_account.bump = _ctx.bumps.movie_account;
+    movie_account.edition = edition;
+    movie_account.seed_hash = MovieAccount::seed_hash(&movie, release_year, edition);
+    movie_account.movie = movie;
+    movie_account.director = legacy.director;
+    movie_account.hero = legacy.hero;
+
+    let movie_stats = &mut _ctx.accounts.movie_stats;
+    movie_stats.bump = _ctx.bumps.movie_stats;
+    movie_stats.movie = movie_account.key();
+
+    emit_event!(_ctx, MovieMigrated {
+        legacy_movie: _ctx.accounts.legacy_movie.key(),
+        movie: _ctx.accounts.movie_account.key(),
+        edition,
+        migrated_by: _ctx.accounts.user.key(),
+    });
+
+    Ok(())
+}
+
+pub fn migrate_review_handler(_ctx: Context<MigrateReview>) -> Result<()> {
+    let legacy_movie = LegacyMovieAccount::read(&_ctx.accounts.legacy_movie)?;
+    let movie_account = &_ctx.accounts.movie_account;
+    if movie_account.seed_hash != MovieAccount::seed_hash(&legacy_movie.movie, legacy_movie.release_year, movie_account.edition) {
+        return Err(MovieReviewSystemError::LegacyMovieMismatch.into());
+    }
+    let legacy = LegacyMovieReview::read(&_ctx.accounts.legacy_review)?;
+    let rating_bps = MovieReview::legacy_to_bps(legacy.movie_rating);
+
+    // The first deployment paid the reward on creation, straight into the legacy vault, so
+    // the review comes over as already rewarded and with nothing to claw back.
+    let now = Clock::get()?.unix_timestamp;
+    let reward_receipt = &mut _ctx.accounts.reward_receipt;
+    reward_receipt.bump = _ctx.bumps.reward_receipt;
+    reward_receipt.is_initialized = true;
+    reward_receipt.movie = movie_account.key();
+    reward_receipt.reviewer = _ctx.accounts.user.key();
+    reward_receipt.amount = 0;
+    reward_receipt.rewarded_at = now;
+    reward_receipt.clawed_back = 0;
+    reward_receipt.epoch_index = None;
+    reward_receipt.cliff_at = now;
+    reward_receipt.end_at = now;
+    reward_receipt.review_generation = 1;
+
+    let movie_review = &mut _ctx.accounts.movie_review;
+    movie_review.set_rating_bps(rating_bps);
+    movie_review.movie_address = movie_account.key();
+    movie_review.review_comment = legacy.review_comment;
+    movie_review.reviewer_name = legacy.reviewer_name;
+    movie_review.bump = _ctx.bumps.movie_review;
+    movie_review.reviewer = legacy.reviewer;
+    movie_review.reward_eligible = false;
+    movie_review.helpful_count = 0;
+    movie_review.unhelpful_count = 0;
+    movie_review.visibility = ReviewVisibility::Visible;
+    movie_review.flag_count = 0;
+    movie_review.aspect_ratings = None;
+    movie_review.created_at = now;
+    movie_review.updated_at = now;
+    movie_review.revision = 0;
+    movie_review.set_labels(ReviewLabels::default());
+    movie_review.generation = 1;
+
+    _ctx.accounts.movie_stats.add_rating(rating_bps)?;
+
+    close_legacy_account(&_ctx.accounts.legacy_review, &_ctx.accounts.user)?;
+
+    emit_event!(_ctx, ReviewMigrated {
+        legacy_review: _ctx.accounts.legacy_review.key(),
+        movie: _ctx.accounts.movie_account.key(),
+        review: _ctx.accounts.movie_review.key(),
+        reviewer: _ctx.accounts.user.key(),
+        rating_bps,
+    });
+
+    Ok(())
+}
 
+/// Closes an account that `Account` cannot deserialize, refunding its rent to `destination`.
+fn close_legacy_account<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = info.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&anchor_lang::system_program::ID);
+    info.realloc(0, false)?;
     Ok(())
 }
 
@@ -34,8 +560,49 @@ pub fn create_review_handler(
     review_comment: String,
     reviewer_name: String,
 ) -> Result<()> {
-    // Implementation for creating a review goes here
+    if !(1..=10).contains(&movie_rating) {
+        return Err(MovieReviewSystemError::InvalidMovieRating.into());
+    }
+
+    create_review_with_bps(
+        _ctx,
+        MovieReview::legacy_to_bps(movie_rating),
+        review_comment,
+        reviewer_name,
+        ReviewLabels::default(),
+        None,
+    )
+}
+
+pub fn create_review_v2_handler(
+    _ctx: Context<CreateReview>,
+    rating_bps: u16,
+    review_comment: String,
+    reviewer_name: String,
+    labels: ReviewLabels,
+    aspect_ratings: Option<AspectRatings>,
+) -> Result<()> {
+    if !_ctx.accounts.config.is_valid_rating_bps(rating_bps) {
+        return Err(MovieReviewSystemError::InvalidRatingBps.into());
+    }
+    if !ContentWarnings::is_valid(labels.content_warnings) {
+        return Err(MovieReviewSystemError::InvalidContentWarnings.into());
+    }
+    if aspect_ratings.is_some_and(|aspects| !aspects.is_valid()) {
+        return Err(MovieReviewSystemError::InvalidAspectRating.into());
+    }
+
+    create_review_with_bps(_ctx, rating_bps, review_comment, reviewer_name, labels, aspect_ratings)
+}
 
+fn create_review_with_bps(
+    _ctx: Context<CreateReview>,
+    rating_bps: u16,
+    review_comment: String,
+    reviewer_name: String,
+    labels: ReviewLabels,
+    aspect_ratings: Option<AspectRatings>,
+) -> Result<()> {
     let movie_review_pda = &_ctx.accounts.movie_review.to_account_info();
     let movie_review = &mut _ctx.accounts.movie_review;
 
@@ -49,53 +616,193 @@ pub fn create_review_handler(
     if reviewer_name.len() > 50 {
         return Err(MovieReviewSystemError::ReviewerNameTooLong.into());
     }
-    if movie_rating < 1 || movie_rating > 10 {
-        return Err(MovieReviewSystemError::InvalidMovieRating.into());
-    }
 
-    movie_review.movie_rating = movie_rating;
+    movie_review.set_rating_bps(rating_bps);
     movie_review.review_comment = review_comment;
     movie_review.reviewer_name = reviewer_name;
     movie_review.movie_address = _ctx.accounts.movie_account.key();
     movie_review.bump = _ctx.bumps.movie_review;
     movie_review.reviewer = _ctx.accounts.user.key();
+    movie_review.helpful_count = 0;
+    movie_review.unhelpful_count = 0;
+    movie_review.visibility = ReviewVisibility::Visible;
+    movie_review.flag_count = 0;
+    movie_review.aspect_ratings = aspect_ratings;
+    let now = Clock::get()?.unix_timestamp;
+    movie_review.created_at = now;
+    movie_review.updated_at = now;
+    movie_review.revision = 0;
+    movie_review.spoiler_forced = false;
+    movie_review.set_labels(labels);
+
+    _ctx.accounts.movie_stats.add_review(&_ctx.accounts.movie_review)?;
 
-    let token_amount = 5000_00_000; // 5000 AST with 6 decimals
+    // A receipt left behind by an earlier, deleted review means this movie already paid out.
+    let reward_eligible = !_ctx.accounts.reward_receipt.is_initialized;
+    _ctx.accounts.movie_review.reward_eligible = reward_eligible;
+
+    let config = &_ctx.accounts.config;
+    let pool_mode = matches!(config.reward_mode, RewardMode::EpochPool { .. });
+    let requested_amount = if reward_eligible && !pool_mode {
+        config
+            .reward_amount(_ctx.accounts.ast_mint.decimals)
+            .ok_or(MovieReviewSystemError::RewardAmountOverflow)?
+    } else {
+        0
+    };
 
     let clock = Clock::get()?;
 
-    if &_ctx.accounts.user_vault.is_initialized == &false {
+    // Once the budget is spent the review still goes through, just without a reward.
+    let emission_state = &mut _ctx.accounts.emission_state;
+    emission_state.roll_epoch(clock.unix_timestamp);
+    let treasury_balance = _ctx.accounts.treasury.amount;
+    let budget_exhausted = requested_amount > 0 && !emission_state.can_emit(requested_amount, treasury_balance);
+    let token_amount = if budget_exhausted { 0 } else { requested_amount };
+    emission_state.record_emission(token_amount)?;
+
+    // Under the epoch pool the review earns a point now and is paid once the epoch is claimed.
+    let mut epoch_point = None;
+    if reward_eligible && pool_mode {
+        let (Some(epoch), Some(epoch_participation)) = (
+            _ctx.accounts.epoch.as_mut(),
+            _ctx.accounts.epoch_participation.as_mut(),
+        ) else {
+            return Err(MovieReviewSystemError::MissingEpochAccounts.into());
+        };
+
+        if !epoch.is_initialized {
+            epoch.bump = _ctx.bumps.epoch.unwrap();
+            epoch.is_initialized = true;
+            epoch.index = emission_state.epoch_index;
+            epoch.started_at = emission_state.epoch_started_at;
+            epoch.ends_at = emission_state.epoch_started_at + emission_state.epoch_duration_seconds;
+        }
+        if epoch.finalized || clock.unix_timestamp >= epoch.ends_at {
+            return Err(MovieReviewSystemError::EpochClosed.into());
+        }
+        if !epoch_participation.is_initialized {
+            epoch_participation.bump = _ctx.bumps.epoch_participation.unwrap();
+            epoch_participation.is_initialized = true;
+            epoch_participation.epoch_index = epoch.index;
+            epoch_participation.reviewer = _ctx.accounts.user.key();
+        }
+
+        epoch.total_points = epoch
+            .total_points
+            .checked_add(1)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        epoch_participation.points = epoch_participation
+            .points
+            .checked_add(1)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        epoch_point = Some((epoch.index, epoch.total_points));
+    }
+
+    let lot = config.new_lot(_ctx.accounts.reward_receipt.key(), token_amount, clock.unix_timestamp);
+    if reward_eligible {
+        let reward_receipt = &mut _ctx.accounts.reward_receipt;
+        reward_receipt.bump = _ctx.bumps.reward_receipt;
+        reward_receipt.is_initialized = true;
+        reward_receipt.movie = _ctx.accounts.movie_account.key();
+        reward_receipt.reviewer = _ctx.accounts.user.key();
+        reward_receipt.amount = token_amount;
+        reward_receipt.rewarded_at = clock.unix_timestamp;
+        reward_receipt.clawed_back = 0;
+        reward_receipt.epoch_index = epoch_point.map(|(epoch_index, _)| epoch_index);
+        reward_receipt.cliff_at = lot.cliff_at;
+        reward_receipt.end_at = lot.end_at;
+    }
+
+    let reward_receipt = &mut _ctx.accounts.reward_receipt;
+    reward_receipt.review_generation = reward_receipt
+        .review_generation
+        .checked_add(1)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+    _ctx.accounts.movie_review.generation = reward_receipt.review_generation;
+
+    if !_ctx.accounts.user_vault.is_initialized {
         let user_vault = &mut _ctx.accounts.user_vault;
         user_vault.bump = _ctx.bumps.user_vault;
         user_vault.user = _ctx.accounts.user.key();
-        user_vault.balance = 0;
         user_vault.is_initialized = true;
-        user_vault.last_withdraw_timestamp = clock.unix_timestamp;
+        user_vault.created_at = clock.unix_timestamp;
         user_vault.withdrawable_amount = 0;
+        user_vault.withdraw_authority = None;
     }
 
-    let mint_authority_seeds: &[&[u8]] = &[b"mint_auth", &[_ctx.bumps.mint_auth]];
+    if token_amount > 0 {
+        let emission_seeds: &[&[u8]] = &[b"emission", &[_ctx.accounts.emission_state.bump]];
 
+        transfer_checked(
+            CpiContext::new_with_signer(
+                _ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: _ctx.accounts.treasury.to_account_info(),
+                    to: _ctx.accounts.ast_token_ata.to_account_info(),
+                    authority: _ctx.accounts.emission_state.to_account_info(),
+                    mint: _ctx.accounts.ast_mint.to_account_info(),
+                },
+                &[emission_seeds],
+            ),
+            token_amount,
+            _ctx.accounts.ast_mint.decimals,
+        )?;
+    }
 
-    mint_to(
-        CpiContext::new_with_signer(
-            _ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: _ctx.accounts.ast_mint.to_account_info(),
-                to: _ctx.accounts.ast_token_ata.to_account_info(),
-                authority: _ctx.accounts.mint_auth.to_account_info(),
-            },
-            &[mint_authority_seeds],
-        ),
-        token_amount,
-    )?;
+    let user_vault = &mut _ctx.accounts.user_vault;
+    if token_amount > 0 {
+        user_vault.push_lot(lot)?;
+    }
+    let vested_amount = user_vault.release_vested(clock.unix_timestamp)?;
 
-    if clock.unix_timestamp.checked_sub(_ctx.accounts.user_vault.last_withdraw_timestamp).unwrap() >= 300 {
-        _ctx.accounts.user_vault.withdrawable_amount = _ctx.accounts.user_vault.withdrawable_amount.checked_add(_ctx.accounts.user_vault.balance).unwrap();
-        _ctx.accounts.user_vault.balance = 0;
-        _ctx.accounts.user_vault.last_withdraw_timestamp = clock.unix_timestamp;
+    let user = _ctx.accounts.user.key();
+    if vested_amount > 0 {
+        emit_event!(_ctx, RewardVested {
+            user,
+            amount: vested_amount,
+            withdrawable_amount: _ctx.accounts.user_vault.withdrawable_amount,
+        });
+    }
+    emit_event!(_ctx, ReviewCreated {
+        movie: _ctx.accounts.movie_account.key(),
+        review: _ctx.accounts.movie_review.key(),
+        reviewer: user,
+        rating: _ctx.accounts.movie_review.movie_rating,
+        rating_bps,
+        reward_eligible,
+        spoiler: labels.spoiler,
+        content_warnings: labels.content_warnings,
+        aspect_ratings,
+    });
+    if let Some((epoch_index, total_points)) = epoch_point {
+        emit_event!(_ctx, EpochPointEarned {
+            user,
+            review: _ctx.accounts.movie_review.key(),
+            epoch_index,
+            total_points,
+        });
+    }
+    if budget_exhausted {
+        emit_event!(_ctx, RewardBudgetExhausted {
+            user,
+            review: _ctx.accounts.movie_review.key(),
+            requested: requested_amount,
+            lifetime_emitted: _ctx.accounts.emission_state.lifetime_emitted,
+            epoch_emitted: _ctx.accounts.emission_state.epoch_emitted,
+            treasury_balance,
+        });
+    }
+    if token_amount > 0 {
+        emit_event!(_ctx, RewardAccrued {
+            user,
+            review: _ctx.accounts.movie_review.key(),
+            amount: token_amount,
+            cliff_at: lot.cliff_at,
+            fully_vested_at: lot.end_at,
+            pending: _ctx.accounts.user_vault.pending_amount(),
+        });
     }
-    _ctx.accounts.user_vault.balance = _ctx.accounts.user_vault.balance.checked_add(token_amount).unwrap();
 
     Ok(())
 }
@@ -105,6 +812,45 @@ pub fn update_review_handler(
     movie_rating: u8,
     review_comment: String,
     reviewer_name: String,
+) -> Result<()> {
+    if !(1..=10).contains(&movie_rating) {
+        return Err(MovieReviewSystemError::InvalidMovieRating.into());
+    }
+
+    // Legacy clients cannot send labels, so the review keeps its current ones.
+    let labels = _ctx.accounts.movie_review.labels();
+    update_review_with_bps(
+        _ctx,
+        MovieReview::legacy_to_bps(movie_rating),
+        review_comment,
+        reviewer_name,
+        labels,
+    )
+}
+
+pub fn update_review_v2_handler(
+    _ctx: Context<UpdateReview>,
+    rating_bps: u16,
+    review_comment: String,
+    reviewer_name: String,
+    labels: ReviewLabels,
+) -> Result<()> {
+    if !_ctx.accounts.config.is_valid_rating_bps(rating_bps) {
+        return Err(MovieReviewSystemError::InvalidRatingBps.into());
+    }
+    if !ContentWarnings::is_valid(labels.content_warnings) {
+        return Err(MovieReviewSystemError::InvalidContentWarnings.into());
+    }
+
+    update_review_with_bps(_ctx, rating_bps, review_comment, reviewer_name, labels)
+}
+
+fn update_review_with_bps(
+    _ctx: Context<UpdateReview>,
+    rating_bps: u16,
+    review_comment: String,
+    reviewer_name: String,
+    labels: ReviewLabels,
 ) -> Result<()> {
     let movie_review_pda = &_ctx.accounts.movie_review.to_account_info();
 
@@ -118,19 +864,331 @@ pub fn update_review_handler(
     if reviewer_name.len() > 50 {
         return Err(MovieReviewSystemError::ReviewerNameTooLong.into());
     }
-    if movie_rating < 1 || movie_rating > 10 {
-        return Err(MovieReviewSystemError::InvalidMovieRating.into());
-    }
 
-    if &_ctx.accounts.movie_review.movie_address.key() != &_ctx.accounts.movie_account.key() {
+    if _ctx.accounts.movie_review.movie_address.key() != _ctx.accounts.movie_account.key() {
         return Err(MovieReviewSystemError::UnauthorizedReviewUpdate.into());
     }
 
+    let now = Clock::get()?.unix_timestamp;
     let movie_review = &mut _ctx.accounts.movie_review;
+    let movie_stats = &mut _ctx.accounts.movie_stats;
+
+    let rating_changed = movie_review.rating_bps != rating_bps;
+    _ctx.accounts.config.edit_rules.check_edit(movie_review, now, rating_changed)?;
+
+    record_revision(_ctx.accounts.review_history.as_deref_mut(), _ctx.bumps.review_history, movie_review);
+
+    let old_rating = movie_review.movie_rating;
+    let old_rating_bps = movie_review.rating_bps;
+    if !movie_review.is_hidden() {
+        movie_stats.remove_rating(old_rating_bps)?;
+        movie_stats.add_rating(rating_bps)?;
+    }
 
-    movie_review.movie_rating = movie_rating;
+    movie_review.set_rating_bps(rating_bps);
     movie_review.review_comment = review_comment;
     movie_review.reviewer_name = reviewer_name;
+    movie_review.set_labels(labels);
+    movie_review.updated_at = now;
+    movie_review.revision = movie_review
+        .revision
+        .checked_add(1)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+
+    emit_event!(_ctx, ReviewUpdated {
+        movie: _ctx.accounts.movie_account.key(),
+        review: _ctx.accounts.movie_review.key(),
+        reviewer: _ctx.accounts.user.key(),
+        old_rating,
+        new_rating: _ctx.accounts.movie_review.movie_rating,
+        old_rating_bps,
+        new_rating_bps: rating_bps,
+        revision: _ctx.accounts.movie_review.revision,
+        spoiler: _ctx.accounts.movie_review.spoiler,
+        content_warnings: _ctx.accounts.movie_review.content_warnings,
+    });
+
+    Ok(())
+}
+
+/// Saves `movie_review`'s current revision before an edit, starting the history over when it
+/// was left behind by a deleted review at the same address.
+fn record_revision(review_history: Option<&mut Account<ReviewHistory>>, bump: Option<u8>, movie_review: &Account<MovieReview>) {
+    if let Some(review_history) = review_history {
+        if !review_history.belongs_to(movie_review) {
+            review_history.bump = bump.unwrap();
+            review_history.is_initialized = true;
+            review_history.review = movie_review.key();
+            review_history.reset(movie_review.generation);
+        }
+        review_history.push(movie_review.revision_entry());
+    }
+}
+
+pub fn flag_review_handler(_ctx: Context<FlagReview>, reason_code: u8) -> Result<()> {
+    let review_flag = &mut _ctx.accounts.review_flag;
+    if review_flag.counts_for(&_ctx.accounts.movie_review) {
+        return Err(MovieReviewSystemError::AlreadyFlagged.into());
+    }
+    review_flag.bump = _ctx.bumps.review_flag;
+    review_flag.is_initialized = true;
+    review_flag.review = _ctx.accounts.movie_review.key();
+    review_flag.flagger = _ctx.accounts.flagger.key();
+    review_flag.reason_code = reason_code;
+    review_flag.flagged_at = Clock::get()?.unix_timestamp;
+    review_flag.review_generation = _ctx.accounts.movie_review.generation;
+
+    let movie_review = &mut _ctx.accounts.movie_review;
+    movie_review.flag_count = movie_review
+        .flag_count
+        .checked_add(1)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+
+    emit_event!(_ctx, ReviewFlagged {
+        review: _ctx.accounts.movie_review.key(),
+        flagger: _ctx.accounts.flagger.key(),
+        reason_code,
+        flag_count: _ctx.accounts.movie_review.flag_count,
+    });
+
+    Ok(())
+}
+
+pub fn hide_review_handler(_ctx: Context<ModerateReview>) -> Result<()> {
+    let movie_review = &mut _ctx.accounts.movie_review;
+    if movie_review.is_hidden() {
+        return Err(MovieReviewSystemError::ReviewAlreadyHidden.into());
+    }
+
+    _ctx.accounts.movie_stats.remove_review(movie_review)?;
+    movie_review.visibility = ReviewVisibility::Hidden;
+
+    emit_event!(_ctx, ReviewHidden {
+        movie: _ctx.accounts.movie_review.movie_address,
+        review: _ctx.accounts.movie_review.key(),
+        moderator: _ctx.accounts.moderator.key(),
+    });
+
+    Ok(())
+}
+
+pub fn restore_review_handler(_ctx: Context<ModerateReview>) -> Result<()> {
+    let movie_review = &mut _ctx.accounts.movie_review;
+    if !movie_review.is_hidden() {
+        return Err(MovieReviewSystemError::ReviewNotHidden.into());
+    }
+
+    _ctx.accounts.movie_stats.add_review(movie_review)?;
+    movie_review.visibility = ReviewVisibility::Visible;
+
+    emit_event!(_ctx, ReviewRestored {
+        movie: _ctx.accounts.movie_review.movie_address,
+        review: _ctx.accounts.movie_review.key(),
+        moderator: _ctx.accounts.moderator.key(),
+    });
+
+    Ok(())
+}
+
+pub fn force_spoiler_handler(_ctx: Context<ModerateReview>, forced: bool) -> Result<()> {
+    let movie_review = &mut _ctx.accounts.movie_review;
+
+    movie_review.spoiler_forced = forced;
+    if forced {
+        movie_review.spoiler = true;
+    }
+
+    emit_event!(_ctx, SpoilerForced {
+        review: _ctx.accounts.movie_review.key(),
+        moderator: _ctx.accounts.moderator.key(),
+        forced,
+    });
+
+    Ok(())
+}
+
+pub fn create_reply_handler(_ctx: Context<CreateReply>, content: String) -> Result<()> {
+    if content.len() > ReviewReply::MAX_CONTENT_LEN {
+        return Err(MovieReviewSystemError::ReplyTooLong.into());
+    }
+
+    let depth = match &_ctx.accounts.parent_reply {
+        Some(parent) => parent
+            .depth
+            .checked_add(1)
+            .filter(|depth| *depth <= ReviewReply::MAX_DEPTH)
+            .ok_or(MovieReviewSystemError::ReplyTooDeep)?,
+        None => 0,
+    };
+    let parent_reply = _ctx.accounts.parent_reply.as_ref().map(|parent| parent.key());
+
+    // A thread left over from a deleted review at this address starts over; the generation
+    // in the reply seeds keeps the new replies clear of the old ones.
+    let generation = _ctx.accounts.movie_review.generation;
+    let review_thread = &mut _ctx.accounts.review_thread;
+    if !review_thread.belongs_to(&_ctx.accounts.movie_review) {
+        review_thread.bump = _ctx.bumps.review_thread;
+        review_thread.is_initialized = true;
+        review_thread.review = _ctx.accounts.movie_review.key();
+        review_thread.reply_count = 0;
+        review_thread.review_generation = generation;
+    }
+    let index = review_thread.reply_count;
+    review_thread.reply_count = index.checked_add(1).ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+
+    let review_reply = &mut _ctx.accounts.review_reply;
+    review_reply.bump = _ctx.bumps.review_reply;
+    review_reply.review = _ctx.accounts.movie_review.key();
+    review_reply.review_generation = generation;
+    review_reply.index = index;
+    review_reply.author = _ctx.accounts.author.key();
+    review_reply.parent_reply = parent_reply;
+    review_reply.depth = depth;
+    review_reply.content = content;
+
+    emit_event!(_ctx, ReplyCreated {
+        review: _ctx.accounts.movie_review.key(),
+        review_generation: generation,
+        reply: _ctx.accounts.review_reply.key(),
+        author: _ctx.accounts.author.key(),
+        parent_reply,
+        depth,
+    });
+
+    Ok(())
+}
+
+pub fn update_reply_handler(_ctx: Context<UpdateReply>, content: String) -> Result<()> {
+    if content.len() > ReviewReply::MAX_CONTENT_LEN {
+        return Err(MovieReviewSystemError::ReplyTooLong.into());
+    }
+
+    _ctx.accounts.review_reply.content = content;
+
+    emit_event!(_ctx, ReplyUpdated {
+        review: _ctx.accounts.review_reply.review,
+        reply: _ctx.accounts.review_reply.key(),
+        author: _ctx.accounts.author.key(),
+    });
+
+    Ok(())
+}
+
+pub fn delete_reply_handler(_ctx: Context<DeleteReply>) -> Result<()> {
+    // Child replies keep pointing at the closed parent; clients render it as deleted.
+    emit_event!(_ctx, ReplyDeleted {
+        review: _ctx.accounts.review_reply.review,
+        reply: _ctx.accounts.review_reply.key(),
+        author: _ctx.accounts.author.key(),
+    });
+
+    Ok(())
+}
+
+pub fn vote_review_handler(_ctx: Context<VoteReview>, helpful: bool) -> Result<()> {
+    let review_vote = &mut _ctx.accounts.review_vote;
+    let movie_review = &mut _ctx.accounts.movie_review;
+
+    // A vote left over from a deleted review at this address starts over.
+    if review_vote.counts_for(movie_review) {
+        if review_vote.helpful == helpful {
+            return Ok(());
+        }
+        movie_review.remove_vote(review_vote.helpful);
+    } else {
+        review_vote.bump = _ctx.bumps.review_vote;
+        review_vote.is_initialized = true;
+        review_vote.review = movie_review.key();
+        review_vote.voter = _ctx.accounts.voter.key();
+        review_vote.review_generation = movie_review.generation;
+    }
+
+    movie_review.add_vote(helpful)?;
+    review_vote.helpful = helpful;
+
+    emit_event!(_ctx, ReviewVoted {
+        review: _ctx.accounts.movie_review.key(),
+        voter: _ctx.accounts.voter.key(),
+        helpful,
+        helpful_count: _ctx.accounts.movie_review.helpful_count,
+        unhelpful_count: _ctx.accounts.movie_review.unhelpful_count,
+    });
+
+    Ok(())
+}
+
+pub fn close_vote_handler(_ctx: Context<CloseVote>) -> Result<()> {
+    let review_info = _ctx.accounts.movie_review.to_account_info();
+    let helpful = _ctx.accounts.review_vote.helpful;
+
+    // The review may already be deleted, or replaced by a newer one the vote never counted
+    // for, in which case there is nothing to undo.
+    if review_info.owner == &crate::ID && !review_info.data_is_empty() {
+        let mut data = review_info.try_borrow_mut_data()?;
+        let mut movie_review = MovieReview::try_deserialize(&mut &data[..])?;
+        if _ctx.accounts.review_vote.counts_for(&movie_review) {
+            movie_review.remove_vote(helpful);
+            movie_review.try_serialize(&mut &mut data[..])?;
+        }
+    }
+
+    emit_event!(_ctx, ReviewVoteClosed {
+        review: _ctx.accounts.review_vote.review,
+        voter: _ctx.accounts.voter.key(),
+        helpful,
+    });
+
+    Ok(())
+}
+
+pub fn set_aspect_ratings_handler(_ctx: Context<UpdateReview>, aspect_ratings: Option<AspectRatings>) -> Result<()> {
+    if aspect_ratings.is_some_and(|aspects| !aspects.is_valid()) {
+        return Err(MovieReviewSystemError::InvalidAspectRating.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let movie_review = &mut _ctx.accounts.movie_review;
+    let movie_stats = &mut _ctx.accounts.movie_stats;
+
+    let rating_changed = movie_review.aspect_ratings != aspect_ratings;
+    _ctx.accounts.config.edit_rules.check_edit(movie_review, now, rating_changed)?;
+
+    record_revision(_ctx.accounts.review_history.as_deref_mut(), _ctx.bumps.review_history, movie_review);
+
+    if !movie_review.is_hidden() {
+        if let Some(old) = &movie_review.aspect_ratings {
+            movie_stats.remove_aspects(old)?;
+        }
+        if let Some(new) = &aspect_ratings {
+            movie_stats.add_aspects(new)?;
+        }
+    }
+    movie_review.aspect_ratings = aspect_ratings;
+    movie_review.updated_at = now;
+    movie_review.revision = movie_review
+        .revision
+        .checked_add(1)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+
+    let rating_bps = movie_review.rating_bps;
+    emit_event!(_ctx, ReviewUpdated {
+        movie: _ctx.accounts.movie_account.key(),
+        review: _ctx.accounts.movie_review.key(),
+        reviewer: _ctx.accounts.user.key(),
+        old_rating: _ctx.accounts.movie_review.movie_rating,
+        new_rating: _ctx.accounts.movie_review.movie_rating,
+        old_rating_bps: rating_bps,
+        new_rating_bps: rating_bps,
+        revision: _ctx.accounts.movie_review.revision,
+        spoiler: _ctx.accounts.movie_review.spoiler,
+        content_warnings: _ctx.accounts.movie_review.content_warnings,
+    });
+    emit_event!(_ctx, AspectRatingsUpdated {
+        movie: _ctx.accounts.movie_account.key(),
+        review: _ctx.accounts.movie_review.key(),
+        reviewer: _ctx.accounts.user.key(),
+        aspect_ratings,
+    });
 
     Ok(())
 }
@@ -140,6 +1198,139 @@ pub fn delete_movie_review_handler(_ctx: Context<DeleteMovieReview>) -> Result<(
         "Deleting movie review account: {}",
         _ctx.accounts.movie_review.key()
     );
+
+    let rating = _ctx.accounts.movie_review.movie_rating;
+    let rating_bps = _ctx.accounts.movie_review.rating_bps;
+    if !_ctx.accounts.movie_review.is_hidden() {
+        _ctx.accounts.movie_stats.remove_review(&_ctx.accounts.movie_review)?;
+    }
+
+    // Take back the review's epoch point. A claimed point has its receipt's epoch cleared and
+    // is clawed back below like a flat reward; an unclaimed one in a finalized epoch forfeits
+    // its share of the pool.
+    if let (true, Some(epoch_index)) = (
+        _ctx.accounts.movie_review.reward_eligible,
+        _ctx.accounts.reward_receipt.epoch_index,
+    ) {
+        let Some(epoch) = _ctx.accounts.epoch.as_mut() else {
+            return Err(MovieReviewSystemError::MissingEpochAccounts.into());
+        };
+        if epoch.index != epoch_index {
+            return Err(MovieReviewSystemError::EpochMismatch.into());
+        }
+
+        let Some(epoch_participation) = _ctx.accounts.epoch_participation.as_mut() else {
+            return Err(MovieReviewSystemError::MissingEpochAccounts.into());
+        };
+        if epoch_participation.epoch_index != epoch_index {
+            return Err(MovieReviewSystemError::EpochMismatch.into());
+        }
+        epoch_participation.points = epoch_participation
+            .points
+            .checked_sub(1)
+            .ok_or(MovieReviewSystemError::EpochMismatch)?;
+
+        if epoch.finalized {
+            // The pool is fixed, so the point's share is forfeited back to the budget.
+            let forfeited = epoch.share(1);
+            let dust = epoch.settle(1, forfeited)?;
+            _ctx.accounts.emission_state.release_reserved(forfeited + dust);
+            emit_event!(_ctx, EpochPointForfeited {
+                user: _ctx.accounts.user.key(),
+                review: _ctx.accounts.movie_review.key(),
+                epoch_index,
+                amount: forfeited,
+            });
+        } else {
+            epoch.total_points = epoch.total_points.saturating_sub(1);
+        }
+        _ctx.accounts.reward_receipt.epoch_index = None;
+
+        if let Some(epoch_participation) = _ctx.accounts.epoch_participation.as_ref() {
+            if epoch_participation.points == 0 {
+                epoch_participation.close(_ctx.accounts.user.to_account_info())?;
+            }
+        }
+    }
+
+    // Claw back whatever part of the review's reward has not vested yet. A vault that is
+    // closed, or was re-created after the reward was paid, no longer holds it: closing
+    // the original vault already forfeited its unvested tokens to the treasury.
+    let clock = Clock::get()?;
+    let receipt = &_ctx.accounts.reward_receipt;
+    let lot = receipt.lot(receipt.key());
+    let unvested = (lot.amount - lot.vested(clock.unix_timestamp)).saturating_sub(receipt.clawed_back);
+    let vault_info = _ctx.accounts.user_vault.to_account_info();
+    let mut clawback = 0;
+    if _ctx.accounts.movie_review.reward_eligible
+        && unvested > 0
+        && vault_info.owner == &crate::ID
+        && !vault_info.data_is_empty()
+    {
+        let mut user_vault = UserVault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+        if user_vault.created_at <= lot.start_at {
+            let vested_amount = user_vault.release_vested(clock.unix_timestamp)?;
+            clawback = user_vault.claw_back(lot.receipt, unvested);
+            user_vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+
+            if vested_amount > 0 {
+                emit_event!(_ctx, RewardVested {
+                    user: _ctx.accounts.user.key(),
+                    amount: vested_amount,
+                    withdrawable_amount: user_vault.withdrawable_amount,
+                });
+            }
+        }
+    }
+
+    if clawback > 0 {
+        let (Some(ast_token_ata), Some(treasury)) = (
+            _ctx.accounts.ast_token_ata.as_ref(),
+            _ctx.accounts.treasury.as_ref(),
+        ) else {
+            return Err(MovieReviewSystemError::MissingClawbackAccounts.into());
+        };
+        let binding = _ctx.accounts.user.key();
+        let user_vault_seeds: &[&[u8]] = &[b"user_vault", binding.as_ref(), &[_ctx.bumps.user_vault]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                _ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ast_token_ata.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: vault_info,
+                    mint: _ctx.accounts.ast_mint.to_account_info(),
+                },
+                &[user_vault_seeds],
+            ),
+            clawback,
+            _ctx.accounts.ast_mint.decimals,
+        )?;
+
+        let rewarded_at = _ctx.accounts.reward_receipt.rewarded_at;
+        _ctx.accounts.emission_state.record_return(clawback, Some(rewarded_at));
+        let reward_receipt = &mut _ctx.accounts.reward_receipt;
+        reward_receipt.clawed_back = reward_receipt
+            .clawed_back
+            .checked_add(clawback)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+
+        emit_event!(_ctx, RewardClawedBack {
+            user: _ctx.accounts.user.key(),
+            review: _ctx.accounts.movie_review.key(),
+            amount: clawback,
+        });
+    }
+
+    emit_event!(_ctx, ReviewDeleted {
+        movie: _ctx.accounts.movie_account.key(),
+        review: _ctx.accounts.movie_review.key(),
+        reviewer: _ctx.accounts.user.key(),
+        rating,
+        rating_bps,
+    });
+
     Ok(())
 }
 
@@ -152,15 +1343,7 @@ pub fn withdraw_tokens_handler(_ctx: Context<WithdrawTokens>) -> Result<()> {
     let user_vault = &mut _ctx.accounts.user_vault;
     let user_ata = &_ctx.accounts.user_ata;
 
-    if clock.unix_timestamp.saturating_sub(user_vault.last_withdraw_timestamp) >= 300 {
-        let new_withdrawable = user_vault
-            .withdrawable_amount
-            .checked_add(user_vault.balance)
-            .unwrap();
-
-        user_vault.withdrawable_amount = new_withdrawable;
-        user_vault.balance = 0;
-    }
+    let vested_amount = user_vault.release_vested(clock.unix_timestamp)?;
 
     let amount = user_vault.withdrawable_amount;
     if amount == 0 {
@@ -183,14 +1366,436 @@ pub fn withdraw_tokens_handler(_ctx: Context<WithdrawTokens>) -> Result<()> {
                 authority: user_vault_info, // ✅ cloned earlier
                 mint: _ctx.accounts.ast_mint.to_account_info(),
             },
-            &[&user_vault_seeds[..]],
+            &[user_vault_seeds],
         ),
         amount,
-        9,
+        _ctx.accounts.ast_mint.decimals,
     )?;
 
     user_vault.withdrawable_amount = 0;
-    user_vault.last_withdraw_timestamp = clock.unix_timestamp;
+
+    let user = _ctx.accounts.user.key();
+    if vested_amount > 0 {
+        emit_event!(_ctx, RewardVested {
+            user,
+            amount: vested_amount,
+            withdrawable_amount: amount,
+        });
+    }
+    emit_event!(_ctx, TokensWithdrawn {
+        user,
+        destination: _ctx.accounts.user_ata.key(),
+        amount,
+    });
 
     Ok(())
 }
+
+pub fn withdraw_tokens_v2_handler(_ctx: Context<WithdrawTokensV2>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Err(MovieReviewSystemError::InvalidWithdrawAmount.into());
+    }
+
+    let clock = Clock::get()?;
+    let user_vault_info = _ctx.accounts.user_vault.to_account_info();
+    let user_vault = &mut _ctx.accounts.user_vault;
+
+    let vested_amount = user_vault.release_vested(clock.unix_timestamp)?;
+    let withdrawable_amount = user_vault.withdrawable_amount;
+
+    if amount > withdrawable_amount {
+        return Err(MovieReviewSystemError::InsufficientVestedBalance.into());
+    }
+
+    let binding = user_vault.user;
+    let user_vault_seeds: &[&[u8]] = &[
+        b"user_vault",
+        binding.as_ref(),
+        &[user_vault.bump],
+    ];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            _ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: _ctx.accounts.ast_token_ata.to_account_info(),
+                to: _ctx.accounts.destination.to_account_info(),
+                authority: user_vault_info,
+                mint: _ctx.accounts.ast_mint.to_account_info(),
+            },
+            &[user_vault_seeds],
+        ),
+        amount,
+        _ctx.accounts.ast_mint.decimals,
+    )?;
+
+    // Anything not requested stays withdrawable for a later call.
+    user_vault.withdrawable_amount -= amount;
+
+    let user = user_vault.user;
+    if vested_amount > 0 {
+        emit_event!(_ctx, RewardVested {
+            user,
+            amount: vested_amount,
+            withdrawable_amount,
+        });
+    }
+    emit_event!(_ctx, TokensWithdrawn {
+        user,
+        destination: _ctx.accounts.destination.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+pub fn set_withdraw_authority_handler(
+    _ctx: Context<ManageWithdrawAuthority>,
+    withdraw_authority: Pubkey,
+) -> Result<()> {
+    _ctx.accounts.user_vault.withdraw_authority = Some(withdraw_authority);
+
+    emit_event!(_ctx, WithdrawAuthorityChanged {
+        user: _ctx.accounts.user_vault.user,
+        withdraw_authority: Some(withdraw_authority),
+    });
+
+    Ok(())
+}
+
+pub fn clear_withdraw_authority_handler(_ctx: Context<ManageWithdrawAuthority>) -> Result<()> {
+    _ctx.accounts.user_vault.withdraw_authority = None;
+
+    emit_event!(_ctx, WithdrawAuthorityChanged {
+        user: _ctx.accounts.user_vault.user,
+        withdraw_authority: None,
+    });
+
+    Ok(())
+}
+
+pub fn close_vault_handler(_ctx: Context<CloseVault>, forfeit_unvested: bool) -> Result<()> {
+    let clock = Clock::get()?;
+    let user_vault_info = _ctx.accounts.user_vault.to_account_info();
+    let user_vault = &mut _ctx.accounts.user_vault;
+
+    let vested_amount = user_vault.release_vested(clock.unix_timestamp)?;
+    let withdrawn = user_vault.withdrawable_amount;
+    // Everything else in the ATA is unvested (or was sent there from outside) and goes back
+    // to the treasury.
+    let forfeited = _ctx.accounts.ast_token_ata.amount.saturating_sub(withdrawn);
+
+    if forfeited > 0 && !forfeit_unvested {
+        return Err(MovieReviewSystemError::UnvestedBalanceRemaining.into());
+    }
+
+    let binding = _ctx.accounts.user.key();
+    let user_vault_seeds: &[&[u8]] = &[
+        b"user_vault",
+        binding.as_ref(),
+        &[user_vault.bump],
+    ];
+
+    if withdrawn > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                _ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: _ctx.accounts.ast_token_ata.to_account_info(),
+                    to: _ctx.accounts.user_ata.to_account_info(),
+                    authority: user_vault_info.clone(),
+                    mint: _ctx.accounts.ast_mint.to_account_info(),
+                },
+                &[user_vault_seeds],
+            ),
+            withdrawn,
+            _ctx.accounts.ast_mint.decimals,
+        )?;
+    }
+
+    if forfeited > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                _ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: _ctx.accounts.ast_token_ata.to_account_info(),
+                    to: _ctx.accounts.treasury.to_account_info(),
+                    authority: user_vault_info.clone(),
+                    mint: _ctx.accounts.ast_mint.to_account_info(),
+                },
+                &[user_vault_seeds],
+            ),
+            forfeited,
+            _ctx.accounts.ast_mint.decimals,
+        )?;
+        _ctx.accounts.emission_state.record_return(forfeited, None);
+    }
+
+    close_account(CpiContext::new_with_signer(
+        _ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: _ctx.accounts.ast_token_ata.to_account_info(),
+            destination: _ctx.accounts.user.to_account_info(),
+            authority: user_vault_info,
+        },
+        &[user_vault_seeds],
+    ))?;
+
+    let user = _ctx.accounts.user.key();
+    if vested_amount > 0 {
+        emit_event!(_ctx, RewardVested {
+            user,
+            amount: vested_amount,
+            withdrawable_amount: withdrawn,
+        });
+    }
+    if withdrawn > 0 {
+        emit_event!(_ctx, TokensWithdrawn {
+            user,
+            destination: _ctx.accounts.user_ata.key(),
+            amount: withdrawn,
+        });
+    }
+    emit_event!(_ctx, VaultClosed {
+        user,
+        withdrawn,
+        forfeited,
+    });
+
+    Ok(())
+}
+
+pub fn migrate_user_vault_handler(_ctx: Context<MigrateUserVault>) -> Result<()> {
+    let vault_info = _ctx.accounts.user_vault.to_account_info();
+    let legacy = {
+        let data = vault_info.try_borrow_data()?;
+        if data.len() != ANCHOR_DISCRIMINATOR_SIZE + LegacyUserVault::SIZE
+            || !data.starts_with(UserVault::DISCRIMINATOR)
+        {
+            return Err(MovieReviewSystemError::AlreadyMigrated.into());
+        }
+        LegacyUserVault::deserialize(&mut &data[ANCHOR_DISCRIMINATOR_SIZE..])?
+    };
+    let user_vault = legacy.migrate(vault_info.key());
+
+    resize_account(
+        &vault_info,
+        &_ctx.accounts.user,
+        &_ctx.accounts.system_program,
+        ANCHOR_DISCRIMINATOR_SIZE + UserVault::INIT_SPACE,
+    )?;
+    user_vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+
+    emit_event!(_ctx, UserVaultMigrated {
+        user: _ctx.accounts.user.key(),
+        pending: legacy.balance,
+        withdrawable_amount: legacy.withdrawable_amount,
+    });
+
+    Ok(())
+}
+
+/// Grows `info` to `len` bytes, with `payer` covering the extra rent.
+fn resize_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    len: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.realloc(len, true)?;
+    Ok(())
+}
+
+pub fn set_reward_mode_handler(_ctx: Context<UpdateRewardConfig>, reward_mode: RewardMode) -> Result<()> {
+    _ctx.accounts.config.reward_mode = reward_mode;
+
+    emit_event!(_ctx, RewardModeUpdated {
+        reward_mode,
+        updated_by: _ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
+pub fn finalize_epoch_handler(_ctx: Context<FinalizeEpoch>) -> Result<()> {
+    let clock = Clock::get()?;
+    let epoch = &mut _ctx.accounts.epoch;
+
+    if epoch.finalized {
+        return Err(MovieReviewSystemError::EpochAlreadyFinalized.into());
+    }
+    if clock.unix_timestamp < epoch.ends_at {
+        return Err(MovieReviewSystemError::EpochNotEnded.into());
+    }
+
+    let pool_per_epoch = match _ctx.accounts.config.reward_mode {
+        RewardMode::EpochPool { pool_per_epoch } => pool_per_epoch,
+        RewardMode::Flat => 0,
+    };
+    let target = 10u64
+        .checked_pow(_ctx.accounts.ast_mint.decimals as u32)
+        .and_then(|scale| pool_per_epoch.checked_mul(scale))
+        .ok_or(MovieReviewSystemError::RewardAmountOverflow)?;
+
+    // The pool is capped by the per-epoch and lifetime budgets and by unreserved treasury funds.
+    let emission_state = &mut _ctx.accounts.emission_state;
+    let pool = if epoch.total_points == 0 {
+        0
+    } else {
+        target
+            .min(emission_state.epoch_cap)
+            .min(emission_state.lifetime_cap.saturating_sub(emission_state.lifetime_emitted))
+            .min(emission_state.available(_ctx.accounts.treasury.amount))
+    };
+
+    emission_state.lifetime_emitted = emission_state
+        .lifetime_emitted
+        .checked_add(pool)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+    emission_state.reserved = emission_state
+        .reserved
+        .checked_add(pool)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+    epoch.pool = pool;
+    epoch.finalized = true;
+
+    emit_event!(_ctx, EpochFinalized {
+        epoch_index: _ctx.accounts.epoch.index,
+        pool,
+        total_points: _ctx.accounts.epoch.total_points,
+    });
+
+    Ok(())
+}
+
+pub fn claim_epoch_rewards_handler(_ctx: Context<ClaimEpochRewards>) -> Result<()> {
+    let clock = Clock::get()?;
+    let epoch = &mut _ctx.accounts.epoch;
+
+    if !epoch.finalized {
+        return Err(MovieReviewSystemError::EpochNotFinalized.into());
+    }
+    if _ctx.remaining_accounts.is_empty() {
+        return Err(MovieReviewSystemError::MissingClaimReceipts.into());
+    }
+
+    if !_ctx.accounts.user_vault.is_initialized {
+        let user_vault = &mut _ctx.accounts.user_vault;
+        user_vault.bump = _ctx.bumps.user_vault;
+        user_vault.user = _ctx.accounts.user.key();
+        user_vault.is_initialized = true;
+        user_vault.created_at = clock.unix_timestamp;
+        user_vault.withdrawable_amount = 0;
+        user_vault.withdraw_authority = None;
+    }
+
+    // Each remaining account is a `RewardReceipt` for one of the user's points in this epoch.
+    // Its share becomes a lot keyed by the receipt, so deleting the review claws it back
+    // like a flat reward. A receipt is claimed once, since its epoch is cleared here.
+    let user = _ctx.accounts.user.key();
+    let share = epoch.share(1);
+    let mut lot = _ctx.accounts.config.new_lot(Pubkey::default(), share, clock.unix_timestamp);
+    let mut points: u64 = 0;
+    for receipt_info in _ctx.remaining_accounts {
+        if receipt_info.owner != &crate::ID || !receipt_info.is_writable {
+            return Err(MovieReviewSystemError::EpochMismatch.into());
+        }
+        let mut receipt = RewardReceipt::try_deserialize(&mut &receipt_info.try_borrow_data()?[..])?;
+        if receipt.reviewer != user || receipt.epoch_index != Some(epoch.index) {
+            return Err(MovieReviewSystemError::EpochMismatch.into());
+        }
+
+        lot.receipt = receipt_info.key();
+        receipt.amount = share;
+        receipt.rewarded_at = clock.unix_timestamp;
+        receipt.cliff_at = lot.cliff_at;
+        receipt.end_at = lot.end_at;
+        receipt.clawed_back = 0;
+        receipt.epoch_index = None;
+        receipt.try_serialize(&mut &mut receipt_info.try_borrow_mut_data()?[..])?;
+
+        if share > 0 {
+            _ctx.accounts.user_vault.push_lot(lot)?;
+        }
+        points += 1;
+    }
+
+    let epoch_participation = &mut _ctx.accounts.epoch_participation;
+    epoch_participation.points = epoch_participation
+        .points
+        .checked_sub(points)
+        .ok_or(MovieReviewSystemError::EpochMismatch)?;
+
+    let amount = share
+        .checked_mul(points)
+        .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+    let dust = epoch.settle(points, amount)?;
+
+    let emission_state = &mut _ctx.accounts.emission_state;
+    emission_state.reserved = emission_state.reserved.saturating_sub(amount);
+    // Rounding dust goes back to the budget once every point has been settled.
+    emission_state.release_reserved(dust);
+
+    if amount > 0 {
+        let emission_seeds: &[&[u8]] = &[b"emission", &[_ctx.accounts.emission_state.bump]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                _ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: _ctx.accounts.treasury.to_account_info(),
+                    to: _ctx.accounts.ast_token_ata.to_account_info(),
+                    authority: _ctx.accounts.emission_state.to_account_info(),
+                    mint: _ctx.accounts.ast_mint.to_account_info(),
+                },
+                &[emission_seeds],
+            ),
+            amount,
+            _ctx.accounts.ast_mint.decimals,
+        )?;
+    }
+
+    // Claimed rewards vest on the configured schedule like flat rewards do.
+    let vested_amount = _ctx.accounts.user_vault.release_vested(clock.unix_timestamp)?;
+
+    if vested_amount > 0 {
+        emit_event!(_ctx, RewardVested {
+            user,
+            amount: vested_amount,
+            withdrawable_amount: _ctx.accounts.user_vault.withdrawable_amount,
+        });
+    }
+    emit_event!(_ctx, EpochRewardsClaimed {
+        user,
+        epoch_index: _ctx.accounts.epoch.index,
+        points,
+        amount,
+        cliff_at: lot.cliff_at,
+        fully_vested_at: lot.end_at,
+    });
+
+    if _ctx.accounts.epoch_participation.points == 0 {
+        _ctx.accounts.epoch_participation.close(_ctx.accounts.user.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+pub fn get_vault_summary_handler(_ctx: Context<GetVaultSummary>) -> Result<VaultSummary> {
+    let clock = Clock::get()?;
+
+    Ok(_ctx.accounts.user_vault.summary(clock.unix_timestamp))
+}
@@ -1,127 +1,432 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::{
     associated_token::*,
+    token_2022::Token2022,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 pub mod state;
-use crate::state::{MovieAccount, MovieReview, UserVault};
+use crate::state::{AspectRatings, ReviewEditRules, ReviewLabels, EmissionState, ReviewHistory, ReviewFlag, ReviewReply, ReviewThread, ReviewVote, Epoch, EpochParticipation, MovieAccount, MovieReview, MovieStats, ProgramConfig, RewardReceipt, Role, RewardMode, RoleGrant, UserVault, VaultSummary, VestingSchedule};
 
 pub mod errors;
 
+pub mod events;
+
 pub mod handlers;
-use crate::handlers::{create_movie_handler, create_review_handler, update_review_handler, delete_movie_review_handler, withdraw_tokens_handler};
+use crate::handlers::{
+    accept_admin_handler, close_vote_handler, force_spoiler_handler, set_review_edit_rules_handler, create_review_v2_handler, set_rating_step_handler, update_review_v2_handler, set_aspect_ratings_handler, flag_review_handler, hide_review_handler, restore_review_handler, create_reply_handler, delete_reply_handler, update_reply_handler, create_movie_handler, vote_review_handler, set_freeze_authority_handler, set_mint_authority_handler, fund_treasury_handler, initialize_reward_mint_handler, initialize_treasury_handler,
+    update_emission_caps_handler, claim_epoch_rewards_handler, finalize_epoch_handler, set_reward_mode_handler, create_review_handler, delete_movie_review_handler,
+    clear_withdraw_authority_handler, close_vault_handler, get_vault_summary_handler, grant_role_handler, migrate_movie_handler, migrate_review_handler, migrate_user_vault_handler,
+    set_withdraw_authority_handler, withdraw_tokens_v2_handler, initialize_config_handler, propose_admin_handler, revoke_role_handler,
+    update_movie_handler, update_review_handler, update_reward_config_handler, withdraw_tokens_handler,
+};
 
 declare_id!("3F4fsF8VBR2sqWMPLLwAuL9ACxwt8QM8HZJdGm9BVJMy");
 
 const ANCHOR_DISCRIMINATOR_SIZE: usize = 8;
 
-const ADMIN_PUBKEY: &str = "EH6KP8sVYg793JEW5EwoELRh1at3sDbPj9RebhFnUR5R";
-
 #[program]
 pub mod movie_review_system {
 
     use super::*;
 
+    /// Creates the singleton config. Only the program upgrade authority may call this.
+    pub fn initialize_config(_ctx: Context<InitializeConfig>, admin: Pubkey) -> Result<()> {
+        initialize_config_handler(_ctx, admin)
+    }
+
+    /// First step of the admin handover; `None` cancels a pending proposal.
+    /// Requires: admin.
+    pub fn propose_admin(_ctx: Context<ProposeAdmin>, new_admin: Option<Pubkey>) -> Result<()> {
+        propose_admin_handler(_ctx, new_admin)
+    }
+
+    /// Second step of the admin handover, signed by the proposed admin.
+    pub fn accept_admin(_ctx: Context<AcceptAdmin>) -> Result<()> {
+        accept_admin_handler(_ctx)
+    }
+
+    /// Requires: admin.
+    pub fn update_reward_config(
+        _ctx: Context<UpdateRewardConfig>,
+        reward_per_review: u64,
+        cooldown_seconds: i64,
+        vesting: VestingSchedule,
+    ) -> Result<()> {
+        update_reward_config_handler(_ctx, reward_per_review, cooldown_seconds, vesting)
+    }
+
+    /// Requires: admin. Creates the Token-2022 AST mint with on-mint metadata, owned by the `mint_auth` PDA.
+    pub fn initialize_reward_mint(
+        _ctx: Context<InitializeRewardMint>,
+        decimals: u8,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        initialize_reward_mint_handler(_ctx, decimals, name, symbol, uri)
+    }
+
+    /// Requires: admin. Creates the emission budget and the treasury that pays rewards.
+    pub fn initialize_treasury(
+        _ctx: Context<InitializeTreasury>,
+        lifetime_cap: u64,
+        epoch_cap: u64,
+        epoch_duration_seconds: i64,
+    ) -> Result<()> {
+        initialize_treasury_handler(_ctx, lifetime_cap, epoch_cap, epoch_duration_seconds)
+    }
+
+    /// Requires: admin.
+    pub fn update_emission_caps(
+        _ctx: Context<UpdateEmissionCaps>,
+        lifetime_cap: u64,
+        epoch_cap: u64,
+        epoch_duration_seconds: i64,
+    ) -> Result<()> {
+        update_emission_caps_handler(_ctx, lifetime_cap, epoch_cap, epoch_duration_seconds)
+    }
+
+    /// Requires: admin. Mints `amount` into the treasury; anyone may also transfer AST in directly.
+    pub fn fund_treasury(_ctx: Context<FundTreasury>, amount: u64) -> Result<()> {
+        fund_treasury_handler(_ctx, amount)
+    }
+
+    /// Requires: admin. Hands minting to another program or multisig; `None` fixes the supply for good.
+    pub fn set_mint_authority(_ctx: Context<ManageMintAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+        set_mint_authority_handler(_ctx, new_authority)
+    }
+
+    /// Requires: admin. `None` clears the freeze authority.
+    pub fn set_freeze_authority(_ctx: Context<ManageMintAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+        set_freeze_authority_handler(_ctx, new_authority)
+    }
+
+    /// Requires: admin. `rating_step_bps` must divide 1000, e.g. 50 for half steps.
+    pub fn set_rating_step(_ctx: Context<UpdateRewardConfig>, rating_step_bps: u16) -> Result<()> {
+        set_rating_step_handler(_ctx, rating_step_bps)
+    }
+
+    /// Requires: admin. Zero fields disable the corresponding rule.
+    pub fn set_review_edit_rules(_ctx: Context<UpdateRewardConfig>, edit_rules: ReviewEditRules) -> Result<()> {
+        set_review_edit_rules_handler(_ctx, edit_rules)
+    }
+
+    /// Requires: admin. Switches between flat per-review rewards and epoch pools.
+    pub fn set_reward_mode(_ctx: Context<UpdateRewardConfig>, reward_mode: RewardMode) -> Result<()> {
+        set_reward_mode_handler(_ctx, reward_mode)
+    }
+
+    /// Requires: admin.
+    pub fn grant_role(_ctx: Context<GrantRole>, member: Pubkey, role: Role) -> Result<()> {
+        grant_role_handler(_ctx, member, role)
+    }
+
+    /// Requires: admin.
+    pub fn revoke_role(_ctx: Context<RevokeRole>) -> Result<()> {
+        revoke_role_handler(_ctx)
+    }
+
+    /// Requires: admin or `Role::Curator`.
+    /// Pass `edition = 0` unless another movie already has the same title and release year.
     pub fn create_movie(
         _ctx: Context<CreateMovie>,
         movie: String,
         director: String,
         hero: String,
         release_year: u16,
+        edition: u8,
+    ) -> Result<()> {
+        create_movie_handler(_ctx, movie, director, hero, release_year, edition)
+    }
+
+    /// Requires: admin or `Role::Curator`. Copies a movie from the first deployment, whose PDA
+    /// was seeded by the raw title, to its seed-hash PDA. The legacy account is kept so its
+    /// reviews can follow with `migrate_review`.
+    pub fn migrate_movie(
+        _ctx: Context<MigrateMovie>,
+        movie: String,
+        release_year: u16,
+        edition: u8,
+    ) -> Result<()> {
+        migrate_movie_handler(_ctx, movie, release_year, edition)
+    }
+
+    /// Requires: admin or `Role::Curator`. `release_year` must match the stored year, since it
+    /// is part of the PDA's seed hash.
+    pub fn update_movie(
+        _ctx: Context<UpdateMovie>,
+        director: String,
+        hero: String,
+        release_year: u16,
     ) -> Result<()> {
-        return create_movie_handler(_ctx, movie, director, hero, release_year);
+        update_movie_handler(_ctx, director, hero, release_year)
     }
 
+    /// Requires: any signer.
     pub fn create_review(
         _ctx: Context<CreateReview>,
         movie_rating: u8,
         review_comment: String,
         reviewer_name: String,
     ) -> Result<()> {
-        return create_review_handler(_ctx, movie_rating, review_comment, reviewer_name);
+        create_review_handler(_ctx, movie_rating, review_comment, reviewer_name)
     }
 
+    /// Requires: any signer. `rating_bps` is on a 0 to 1000 scale in multiples of the configured step.
+    /// `labels` carries the spoiler flag and `ContentWarnings` bits. `aspect_ratings` are set
+    /// with the review, so attaching them does not count as an edit.
+    pub fn create_review_v2(
+        _ctx: Context<CreateReview>,
+        rating_bps: u16,
+        review_comment: String,
+        reviewer_name: String,
+        labels: ReviewLabels,
+        aspect_ratings: Option<AspectRatings>,
+    ) -> Result<()> {
+        create_review_v2_handler(_ctx, rating_bps, review_comment, reviewer_name, labels, aspect_ratings)
+    }
+
+    /// Requires: the review author.
     pub fn update_review(
         _ctx: Context<UpdateReview>,
         movie_rating: u8,
         review_comment: String,
         reviewer_name: String,
     ) -> Result<()> {
-        return update_review_handler(_ctx, movie_rating, review_comment, reviewer_name);
+        update_review_handler(_ctx, movie_rating, review_comment, reviewer_name)
+    }
+
+    /// Requires: the review author, on a review in the current layout; a legacy review is
+    /// moved first with `migrate_review`. A spoiler flag forced by a moderator stays set
+    /// regardless of `labels`.
+    pub fn update_review_v2(
+        _ctx: Context<UpdateReview>,
+        rating_bps: u16,
+        review_comment: String,
+        reviewer_name: String,
+        labels: ReviewLabels,
+    ) -> Result<()> {
+        update_review_v2_handler(_ctx, rating_bps, review_comment, reviewer_name, labels)
+    }
+
+    /// Requires: the review author. Changes or, with `None`, clears the per-aspect scores set at
+    /// creation; counts as an edit under `ReviewEditRules`.
+    pub fn set_aspect_ratings(_ctx: Context<UpdateReview>, aspect_ratings: Option<AspectRatings>) -> Result<()> {
+        set_aspect_ratings_handler(_ctx, aspect_ratings)
+    }
+
+    /// Requires: the review author. Moves a review from the first deployment onto its migrated
+    /// movie and closes the legacy account. Its reward was paid at creation, so the new review
+    /// is not reward eligible.
+    pub fn migrate_review(_ctx: Context<MigrateReview>) -> Result<()> {
+        migrate_review_handler(_ctx)
     }
 
+    /// Requires: the review author. Claws back the review's reward if it has not vested yet.
     pub fn delete_movie_review(_ctx: Context<DeleteMovieReview>) -> Result<()> {
-        return delete_movie_review_handler(_ctx);
+        delete_movie_review_handler(_ctx)
     }
-    
+
+    /// Requires: any signer, once per review. `reason_code` is interpreted off-chain.
+    pub fn flag_review(_ctx: Context<FlagReview>, reason_code: u8) -> Result<()> {
+        flag_review_handler(_ctx, reason_code)
+    }
+
+    /// Requires: admin or `Role::Moderator`. Takes the review's rating out of the movie's stats.
+    pub fn hide_review(_ctx: Context<ModerateReview>) -> Result<()> {
+        hide_review_handler(_ctx)
+    }
+
+    /// Requires: admin or `Role::Moderator`. `forced = false` lets the author clear the flag again.
+    pub fn force_spoiler(_ctx: Context<ModerateReview>, forced: bool) -> Result<()> {
+        force_spoiler_handler(_ctx, forced)
+    }
+
+    /// Requires: admin or `Role::Moderator`.
+    pub fn restore_review(_ctx: Context<ModerateReview>) -> Result<()> {
+        restore_review_handler(_ctx)
+    }
+
+    /// Requires: any signer. Pass `parent_reply` to answer another reply instead of the review.
+    pub fn create_reply(_ctx: Context<CreateReply>, content: String) -> Result<()> {
+        create_reply_handler(_ctx, content)
+    }
+
+    /// Requires: the reply author.
+    pub fn update_reply(_ctx: Context<UpdateReply>, content: String) -> Result<()> {
+        update_reply_handler(_ctx, content)
+    }
+
+    /// Requires: the reply author.
+    pub fn delete_reply(_ctx: Context<DeleteReply>) -> Result<()> {
+        delete_reply_handler(_ctx)
+    }
+
+    /// Requires: any signer except the review author. Voting again with the other value flips the vote.
+    pub fn vote_review(_ctx: Context<VoteReview>, helpful: bool) -> Result<()> {
+        vote_review_handler(_ctx, helpful)
+    }
+
+    /// Requires: the voter. Reclaims the vote's rent and takes the vote off the review.
+    pub fn close_vote(_ctx: Context<CloseVote>) -> Result<()> {
+        close_vote_handler(_ctx)
+    }
+
+    /// Requires: the vault owner, and no withdraw authority set on the vault.
     pub fn withdraw_tokens(_ctx: Context<WithdrawTokens>) -> Result<()> {
-        return withdraw_tokens_handler(_ctx);
+        withdraw_tokens_handler(_ctx)
+    }
+
+    /// Requires: the vault's withdraw authority, or the owner when none is set.
+    /// Withdraws `amount` of the vested balance to any AST account.
+    pub fn withdraw_tokens_v2(_ctx: Context<WithdrawTokensV2>, amount: u64) -> Result<()> {
+        withdraw_tokens_v2_handler(_ctx, amount)
+    }
+
+    /// Requires: the vault's withdraw authority, or the owner when none is set.
+    pub fn set_withdraw_authority(
+        _ctx: Context<ManageWithdrawAuthority>,
+        withdraw_authority: Pubkey,
+    ) -> Result<()> {
+        set_withdraw_authority_handler(_ctx, withdraw_authority)
+    }
+
+    /// Requires: the vault's withdraw authority, or the owner when none is set.
+    pub fn clear_withdraw_authority(_ctx: Context<ManageWithdrawAuthority>) -> Result<()> {
+        clear_withdraw_authority_handler(_ctx)
+    }
+
+    /// Requires: the vault owner, with no withdraw authority set. Sweeps vested tokens to the
+    /// owner, returns unvested ones to the treasury only when `forfeit_unvested` is set, and
+    /// returns all rent.
+    pub fn close_vault(_ctx: Context<CloseVault>, forfeit_unvested: bool) -> Result<()> {
+        close_vault_handler(_ctx, forfeit_unvested)
+    }
+
+    /// Requires: the vault owner. Moves a vault created before reward lots to the current
+    /// layout; the legacy cooldown balance becomes a lot. The owner pays the extra rent.
+    pub fn migrate_user_vault(_ctx: Context<MigrateUserVault>) -> Result<()> {
+        migrate_user_vault_handler(_ctx)
+    }
+
+    /// Requires: nothing. Permissionless crank that closes an ended epoch and fixes its pool.
+    pub fn finalize_epoch(_ctx: Context<FinalizeEpoch>) -> Result<()> {
+        finalize_epoch_handler(_ctx)
+    }
+
+    /// Requires: a reviewer with points in the finalized epoch. Pass the `RewardReceipt` of
+    /// each review being claimed as a writable remaining account; each pays one point. The
+    /// participation closes once all its points are claimed.
+    pub fn claim_epoch_rewards(_ctx: Context<ClaimEpochRewards>) -> Result<()> {
+        claim_epoch_rewards_handler(_ctx)
+    }
+
+    /// View: pending and withdrawable totals for a vault. Requires: nothing.
+    pub fn get_vault_summary(_ctx: Context<GetVaultSummary>) -> Result<VaultSummary> {
+        get_vault_summary_handler(_ctx)
     }
 
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-#[instruction(movie: String)]
-pub struct CreateMovie<'info> {
+pub struct InitializeConfig<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub authority: Signer<'info>,
 
     #[account(
         init,
-        payer = user,
-        space = ANCHOR_DISCRIMINATOR_SIZE + MovieAccount::INIT_SPACE + movie.len(),
-        seeds = [b"movie", movie.as_bytes()],
+        payer = authority,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ProgramConfig::INIT_SPACE,
+        seeds = [b"config"],
         bump
     )]
-    pub movie_account: Account<'info, MovieAccount>,
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Not named `program`, which `event_cpi` claims for itself.
+    #[account(constraint = movie_review_program.programdata_address()? == Some(program_data.key()))]
+    pub movie_review_program: Program<'info, crate::program::MovieReviewSystem>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ errors::MovieReviewSystemError::InvalidConfigInitializer
+    )]
+    pub program_data: Account<'info, ProgramData>,
 
     pub system_program: Program<'info, System>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-pub struct CreateReview<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
 
     #[account(
-        seeds = [b"movie", movie_account.movie.as_bytes()],
-        bump = movie_account.bump,
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
     )]
-    pub movie_account: Account<'info, MovieAccount>,
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
 
     #[account(
-        init,
-        payer = user,
-        space = ANCHOR_DISCRIMINATOR_SIZE + MovieReview::INIT_SPACE,
-        seeds = [b"review", movie_account.key().as_ref(), user.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(new_admin.key()) @ errors::MovieReviewSystemError::InvalidPendingAdmin,
     )]
-    pub movie_review: Account<'info, state::MovieReview>,
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateRewardConfig<'info> {
+    pub admin: Signer<'info>,
 
     #[account(
-        init_if_needed,
-        payer = user,
-        seeds = [b"user_vault", user.key().as_ref()],
-        bump,
-        space = ANCHOR_DISCRIMINATOR_SIZE + UserVault::INIT_SPACE,
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
     )]
-    pub user_vault: Account<'info, UserVault>,
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(decimals: u8)]
+pub struct InitializeRewardMint<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
 
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = ast_mint,
-        associated_token::authority = user_vault,
-        associated_token::token_program = token_program,
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
     )]
-    pub ast_token_ata: InterfaceAccount<'info, TokenAccount>,
+    pub config: Account<'info, ProgramConfig>,
 
     #[account(
-        mut,
-        constraint = ast_mint.mint_authority.unwrap() == mint_auth.key() @ errors::MovieReviewSystemError::InvalidMintAuthority
+        init,
+        payer = admin,
+        seeds = [b"reward_mint"],
+        bump,
+        mint::decimals = decimals,
+        mint::authority = mint_auth,
+        mint::freeze_authority = mint_auth,
+        mint::token_program = token_program,
+        extensions::metadata_pointer::authority = mint_auth,
+        extensions::metadata_pointer::metadata_address = reward_mint,
     )]
-    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         seeds = [b"mint_auth"],
         bump
@@ -129,83 +434,124 @@ pub struct CreateReview<'info> {
     /// CHECK: PDA authority for minting
     pub mint_auth: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program: Program<'info, Token2022>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-pub struct UpdateReview<'info> {
+pub struct InitializeTreasury<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub admin: Signer<'info>,
 
     #[account(
-        seeds = [b"movie", movie_account.movie.as_bytes()],
-        bump = movie_account.bump,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
     )]
-    pub movie_account: Account<'info, MovieAccount>,
+    pub config: Account<'info, ProgramConfig>,
 
     #[account(
-        mut,
-        seeds = [b"review", movie_account.key().as_ref(), user.key().as_ref()],
-        bump = movie_review.bump,
+        init,
+        payer = admin,
+        space = ANCHOR_DISCRIMINATOR_SIZE + EmissionState::INIT_SPACE,
+        seeds = [b"emission"],
+        bump
     )]
-    pub movie_review: Account<'info, MovieReview>,
+    pub emission_state: Account<'info, EmissionState>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"treasury"],
+        bump,
+        token::mint = ast_mint,
+        token::authority = emission_state,
+        token::token_program = token_program,
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = config.reward_mint == Some(ast_mint.key()) @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-pub struct DeleteMovieReview<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+pub struct UpdateEmissionCaps<'info> {
+    pub admin: Signer<'info>,
 
     #[account(
-        mut,
-        seeds = [b"review", movie_account.key().as_ref(), user.key().as_ref()],
-        bump = movie_review.bump,
-        close = user,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
     )]
-    pub movie_review: Account<'info, MovieReview>,
+    pub config: Account<'info, ProgramConfig>,
 
     #[account(
-        seeds = [b"movie", movie_account.movie.as_bytes()],
-        bump = movie_account.bump,
+        mut,
+        seeds = [b"emission"],
+        bump = emission_state.bump,
     )]
-    pub movie_account: Account<'info, MovieAccount>,
+    pub emission_state: Account<'info, EmissionState>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-pub struct WithdrawTokens<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+pub struct FundTreasury<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [b"emission"],
+        bump = emission_state.bump,
+    )]
+    pub emission_state: Account<'info, EmissionState>,
 
     #[account(
         mut,
-        seeds = [b"user_vault", user.key().as_ref()],
-        bump = user_vault.bump,
-        constraint = user_vault.user == user.key() @ errors::MovieReviewSystemError::InvalidUserToCreateMovie,
+        address = emission_state.treasury,
     )]
-    pub user_vault: Account<'info, UserVault>,
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = ast_mint,
-        associated_token::authority = user_vault,
-        associated_token::token_program = token_program,
+        mut,
+        address = emission_state.mint @ errors::MovieReviewSystemError::InvalidRewardMint,
+        constraint = ast_mint.mint_authority == COption::Some(mint_auth.key()) @ errors::MovieReviewSystemError::InvalidMintAuthority
     )]
-    pub ast_token_ata: InterfaceAccount<'info, TokenAccount>,
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [b"mint_auth"],
+        bump
+    )]
+    /// CHECK: PDA authority for minting
+    pub mint_auth: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ManageMintAuthority<'info> {
+    pub admin: Signer<'info>,
 
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = ast_mint,
-        associated_token::authority = user,
-        associated_token::token_program = token_program,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
     )]
-    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+    pub config: Account<'info, ProgramConfig>,
 
     #[account(
         mut,
-        constraint = ast_mint.mint_authority.unwrap() == mint_auth.key() @ errors::MovieReviewSystemError::InvalidMintAuthority
+        constraint = config.reward_mint == Some(ast_mint.key()) @ errors::MovieReviewSystemError::InvalidRewardMint,
     )]
     pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
@@ -214,8 +560,938 @@ pub struct WithdrawTokens<'info> {
     )]
     /// CHECK: PDA authority for minting
     pub mint_auth: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(member: Pubkey, role: Role)]
+pub struct GrantRole<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ANCHOR_DISCRIMINATOR_SIZE + RoleGrant::INIT_SPACE,
+        seeds = [b"role", member.as_ref(), &[role as u8]],
+        bump
+    )]
+    pub role_grant: Account<'info, RoleGrant>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ errors::MovieReviewSystemError::UnauthorizedAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
 
-}
\ No newline at end of file
+    #[account(
+        mut,
+        seeds = [b"role", role_grant.member.as_ref(), &[role_grant.role as u8]],
+        bump = role_grant.bump,
+        close = admin,
+    )]
+    pub role_grant: Account<'info, RoleGrant>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(movie: String, director: String, hero: String, release_year: u16, edition: u8)]
+pub struct CreateMovie<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authorizes(user.key(), role_grant.as_deref(), Role::Curator) @ errors::MovieReviewSystemError::UnauthorizedRole,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MovieAccount::space(&movie, &director, &hero),
+        seeds = [b"movie", MovieAccount::seed_hash(&movie, release_year, edition).as_ref()],
+        bump
+    )]
+    pub movie_account: Account<'info, MovieAccount>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MovieStats::INIT_SPACE,
+        seeds = [b"movie_stats", movie_account.key().as_ref()],
+        bump
+    )]
+    pub movie_stats: Account<'info, MovieStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(director: String, hero: String)]
+pub struct UpdateMovie<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authorizes(user.key(), role_grant.as_deref(), Role::Curator) @ errors::MovieReviewSystemError::UnauthorizedRole,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+
+    #[account(
+        mut,
+        seeds = [b"movie", movie_account.seed_hash.as_ref()],
+        bump = movie_account.bump,
+        realloc = ANCHOR_DISCRIMINATOR_SIZE + MovieAccount::space(&movie_account.movie, &director, &hero),
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub movie_account: Account<'info, MovieAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CreateReview<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [b"movie", movie_account.seed_hash.as_ref()],
+        bump = movie_account.bump,
+    )]
+    pub movie_account: Account<'info, MovieAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"movie_stats", movie_account.key().as_ref()],
+        bump = movie_stats.bump,
+    )]
+    pub movie_stats: Account<'info, MovieStats>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MovieReview::INIT_SPACE,
+        seeds = [b"review", movie_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub movie_review: Account<'info, state::MovieReview>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + RewardReceipt::INIT_SPACE,
+        seeds = [b"reward_receipt", movie_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub reward_receipt: Account<'info, RewardReceipt>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"user_vault", user.key().as_ref()],
+        bump,
+        space = ANCHOR_DISCRIMINATOR_SIZE + UserVault::INIT_SPACE,
+    )]
+    pub user_vault: Box<Account<'info, UserVault>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub ast_token_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"emission"],
+        bump = emission_state.bump,
+    )]
+    pub emission_state: Box<Account<'info, EmissionState>>,
+
+    /// Required under `RewardMode::EpochPool`.
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Epoch::INIT_SPACE,
+        seeds = [b"epoch", emission_state.current_epoch_index(Clock::get()?.unix_timestamp).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub epoch: Option<Box<Account<'info, Epoch>>>,
+
+    /// Required under `RewardMode::EpochPool`.
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + EpochParticipation::INIT_SPACE,
+        seeds = [
+            b"epoch_points",
+            emission_state.current_epoch_index(Clock::get()?.unix_timestamp).to_le_bytes().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_participation: Option<Box<Account<'info, EpochParticipation>>>,
+
+    #[account(
+        mut,
+        address = emission_state.treasury,
+    )]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = emission_state.mint @ errors::MovieReviewSystemError::InvalidRewardMint,
+        constraint = config.reward_mint == Some(ast_mint.key()) @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateReview<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [b"movie", movie_account.seed_hash.as_ref()],
+        bump = movie_account.bump,
+    )]
+    pub movie_account: Account<'info, MovieAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"movie_stats", movie_account.key().as_ref()],
+        bump = movie_stats.bump,
+    )]
+    pub movie_stats: Account<'info, MovieStats>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie_account.key().as_ref(), user.key().as_ref()],
+        bump = movie_review.bump,
+    )]
+    pub movie_review: Account<'info, MovieReview>,
+
+    /// Pass to record the revision being replaced; created on first use.
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ReviewHistory::INIT_SPACE,
+        seeds = [b"history", movie_review.key().as_ref()],
+        bump
+    )]
+    pub review_history: Option<Box<Account<'info, ReviewHistory>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct FlagReview<'info> {
+    #[account(mut)]
+    pub flagger: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie_review.movie_address.as_ref(), movie_review.reviewer.as_ref()],
+        bump = movie_review.bump,
+    )]
+    pub movie_review: Account<'info, MovieReview>,
+
+    #[account(
+        init_if_needed,
+        payer = flagger,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ReviewFlag::INIT_SPACE,
+        seeds = [b"flag", movie_review.key().as_ref(), flagger.key().as_ref()],
+        bump
+    )]
+    pub review_flag: Account<'info, ReviewFlag>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ModerateReview<'info> {
+    pub moderator: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authorizes(moderator.key(), role_grant.as_deref(), Role::Moderator) @ errors::MovieReviewSystemError::UnauthorizedRole,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie_review.movie_address.as_ref(), movie_review.reviewer.as_ref()],
+        bump = movie_review.bump,
+    )]
+    pub movie_review: Account<'info, MovieReview>,
+
+    #[account(
+        mut,
+        seeds = [b"movie_stats", movie_review.movie_address.as_ref()],
+        bump = movie_stats.bump,
+    )]
+    pub movie_stats: Account<'info, MovieStats>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CreateReply<'info> {
+    #[account(mut)]
+    pub author: Signer<'info>,
+
+    #[account(
+        seeds = [b"review", movie_review.movie_address.as_ref(), movie_review.reviewer.as_ref()],
+        bump = movie_review.bump,
+    )]
+    pub movie_review: Account<'info, MovieReview>,
+
+    #[account(
+        init_if_needed,
+        payer = author,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ReviewThread::INIT_SPACE,
+        seeds = [b"thread", movie_review.key().as_ref()],
+        bump
+    )]
+    pub review_thread: Account<'info, ReviewThread>,
+
+    #[account(
+        constraint = parent_reply.review == movie_review.key() @ errors::MovieReviewSystemError::InvalidParentReply,
+        constraint = parent_reply.belongs_to(&movie_review) @ errors::MovieReviewSystemError::InvalidParentReply,
+    )]
+    pub parent_reply: Option<Account<'info, ReviewReply>>,
+
+    #[account(
+        init,
+        payer = author,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ReviewReply::INIT_SPACE,
+        seeds = [
+            b"reply",
+            movie_review.key().as_ref(),
+            movie_review.generation.to_le_bytes().as_ref(),
+            review_thread.next_reply_index(&movie_review).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub review_reply: Account<'info, ReviewReply>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateReply<'info> {
+    pub author: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"reply",
+            review_reply.review.as_ref(),
+            review_reply.review_generation.to_le_bytes().as_ref(),
+            review_reply.index.to_le_bytes().as_ref(),
+        ],
+        bump = review_reply.bump,
+        has_one = author @ errors::MovieReviewSystemError::UnauthorizedReplyUpdate,
+    )]
+    pub review_reply: Account<'info, ReviewReply>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DeleteReply<'info> {
+    #[account(mut)]
+    pub author: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"reply",
+            review_reply.review.as_ref(),
+            review_reply.review_generation.to_le_bytes().as_ref(),
+            review_reply.index.to_le_bytes().as_ref(),
+        ],
+        bump = review_reply.bump,
+        has_one = author @ errors::MovieReviewSystemError::UnauthorizedReplyUpdate,
+        close = author,
+    )]
+    pub review_reply: Account<'info, ReviewReply>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct VoteReview<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie_review.movie_address.as_ref(), movie_review.reviewer.as_ref()],
+        bump = movie_review.bump,
+        constraint = movie_review.reviewer != voter.key() @ errors::MovieReviewSystemError::SelfVote,
+    )]
+    pub movie_review: Account<'info, MovieReview>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ReviewVote::INIT_SPACE,
+        seeds = [b"vote", movie_review.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub review_vote: Account<'info, ReviewVote>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CloseVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", review_vote.review.as_ref(), voter.key().as_ref()],
+        bump = review_vote.bump,
+        close = voter,
+    )]
+    pub review_vote: Account<'info, ReviewVote>,
+
+    /// CHECK: the voted review; may already be closed, so it is only deserialized when still live.
+    #[account(
+        mut,
+        address = review_vote.review,
+    )]
+    pub movie_review: UncheckedAccount<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(movie: String, release_year: u16, edition: u8)]
+pub struct MigrateMovie<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.authorizes(user.key(), role_grant.as_deref(), Role::Curator) @ errors::MovieReviewSystemError::UnauthorizedRole,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub role_grant: Option<Account<'info, RoleGrant>>,
+
+    /// CHECK: a movie in the legacy layout, which `Account` cannot deserialize; the handler
+    /// checks it against its legacy PDA.
+    pub legacy_movie: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MovieAccount::INIT_SPACE,
+        seeds = [b"movie", MovieAccount::seed_hash(&movie, release_year, edition).as_ref()],
+        bump
+    )]
+    pub movie_account: Account<'info, MovieAccount>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MovieStats::INIT_SPACE,
+        seeds = [b"movie_stats", movie_account.key().as_ref()],
+        bump
+    )]
+    pub movie_stats: Account<'info, MovieStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct MigrateReview<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: the review's movie in the legacy layout; the handler checks it against its
+    /// legacy PDA and `movie_account`.
+    pub legacy_movie: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"movie", movie_account.seed_hash.as_ref()],
+        bump = movie_account.bump,
+    )]
+    pub movie_account: Account<'info, MovieAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"movie_stats", movie_account.key().as_ref()],
+        bump = movie_stats.bump,
+    )]
+    pub movie_stats: Account<'info, MovieStats>,
+
+    /// CHECK: a review in the legacy layout; the handler checks the discriminator and size.
+    #[account(
+        mut,
+        seeds = [b"review", legacy_movie.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub legacy_review: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MovieReview::INIT_SPACE,
+        seeds = [b"review", movie_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub movie_review: Box<Account<'info, MovieReview>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + RewardReceipt::INIT_SPACE,
+        seeds = [b"reward_receipt", movie_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub reward_receipt: Account<'info, RewardReceipt>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DeleteMovieReview<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie_account.key().as_ref(), user.key().as_ref()],
+        bump = movie_review.bump,
+        close = user,
+    )]
+    pub movie_review: Account<'info, MovieReview>,
+
+    #[account(
+        mut,
+        seeds = [b"history", movie_review.key().as_ref()],
+        bump = review_history.bump,
+        close = user,
+    )]
+    pub review_history: Option<Box<Account<'info, ReviewHistory>>>,
+
+    #[account(
+        seeds = [b"movie", movie_account.seed_hash.as_ref()],
+        bump = movie_account.bump,
+    )]
+    pub movie_account: Account<'info, MovieAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"movie_stats", movie_account.key().as_ref()],
+        bump = movie_stats.bump,
+    )]
+    pub movie_stats: Account<'info, MovieStats>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_receipt", movie_account.key().as_ref(), user.key().as_ref()],
+        bump = reward_receipt.bump,
+    )]
+    pub reward_receipt: Account<'info, RewardReceipt>,
+
+    /// CHECK: the reviewer's vault; may be closed, and is only deserialized when there is
+    /// an unvested reward to claw back.
+    #[account(
+        mut,
+        seeds = [b"user_vault", user.key().as_ref()],
+        bump,
+    )]
+    pub user_vault: UncheckedAccount<'info>,
+
+    /// Required when part of the reward is clawed back.
+    #[account(
+        mut,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub ast_token_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"emission"],
+        bump = emission_state.bump,
+    )]
+    pub emission_state: Box<Account<'info, EmissionState>>,
+
+    /// Required when the review earned a point in an epoch.
+    #[account(
+        mut,
+        seeds = [b"epoch", epoch.index.to_le_bytes().as_ref()],
+        bump = epoch.bump,
+    )]
+    pub epoch: Option<Box<Account<'info, Epoch>>>,
+
+    /// Required when the review earned a point in an epoch that has not been claimed.
+    #[account(
+        mut,
+        seeds = [b"epoch_points", epoch_participation.epoch_index.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = epoch_participation.bump,
+    )]
+    pub epoch_participation: Option<Box<Account<'info, EpochParticipation>>>,
+
+    /// Required when part of the reward is clawed back.
+    #[account(
+        mut,
+        address = emission_state.treasury,
+    )]
+    pub treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        address = emission_state.mint @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct WithdrawTokens<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_vault", user.key().as_ref()],
+        bump = user_vault.bump,
+        constraint = user_vault.withdraw_signer() == user.key() @ errors::MovieReviewSystemError::UnauthorizedWithdrawAuthority,
+    )]
+    pub user_vault: Box<Account<'info, UserVault>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub ast_token_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = config.reward_mint == Some(ast_mint.key()) @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct WithdrawTokensV2<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_vault", user_vault.user.as_ref()],
+        bump = user_vault.bump,
+        constraint = user_vault.withdraw_signer() == authority.key() @ errors::MovieReviewSystemError::UnauthorizedWithdrawAuthority,
+    )]
+    pub user_vault: Box<Account<'info, UserVault>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub ast_token_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// Any AST token account, e.g. a cold wallet's.
+    #[account(
+        mut,
+        token::mint = ast_mint,
+        token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = config.reward_mint == Some(ast_mint.key()) @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ManageWithdrawAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_vault", user_vault.user.as_ref()],
+        bump = user_vault.bump,
+        constraint = user_vault.withdraw_signer() == authority.key() @ errors::MovieReviewSystemError::UnauthorizedWithdrawAuthority,
+    )]
+    pub user_vault: Box<Account<'info, UserVault>>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_vault", user.key().as_ref()],
+        bump = user_vault.bump,
+        constraint = user_vault.withdraw_authority.is_none() @ errors::MovieReviewSystemError::WithdrawAuthorityStillSet,
+        close = user,
+    )]
+    pub user_vault: Box<Account<'info, UserVault>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub ast_token_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"emission"],
+        bump = emission_state.bump,
+    )]
+    pub emission_state: Box<Account<'info, EmissionState>>,
+
+    #[account(
+        mut,
+        address = emission_state.treasury,
+    )]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = emission_state.mint @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct MigrateUserVault<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: a vault in the legacy layout, which `Account` cannot deserialize; the handler
+    /// checks the discriminator and size.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"user_vault", user.key().as_ref()],
+        bump,
+    )]
+    pub user_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct FinalizeEpoch<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"emission"],
+        bump = emission_state.bump,
+    )]
+    pub emission_state: Box<Account<'info, EmissionState>>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", epoch.index.to_le_bytes().as_ref()],
+        bump = epoch.bump,
+    )]
+    pub epoch: Box<Account<'info, Epoch>>,
+
+    #[account(address = emission_state.treasury)]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = emission_state.mint @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ClaimEpochRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"emission"],
+        bump = emission_state.bump,
+    )]
+    pub emission_state: Box<Account<'info, EmissionState>>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", epoch.index.to_le_bytes().as_ref()],
+        bump = epoch.bump,
+    )]
+    pub epoch: Box<Account<'info, Epoch>>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch_points", epoch.index.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = epoch_participation.bump,
+    )]
+    pub epoch_participation: Box<Account<'info, EpochParticipation>>,
+
+    #[account(
+        mut,
+        address = emission_state.treasury,
+    )]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"user_vault", user.key().as_ref()],
+        bump,
+        space = ANCHOR_DISCRIMINATOR_SIZE + UserVault::INIT_SPACE,
+    )]
+    pub user_vault: Box<Account<'info, UserVault>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = ast_mint,
+        associated_token::authority = user_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub ast_token_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = emission_state.mint @ errors::MovieReviewSystemError::InvalidRewardMint,
+    )]
+    pub ast_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct GetVaultSummary<'info> {
+    #[account(
+        seeds = [b"user_vault", user_vault.user.as_ref()],
+        bump = user_vault.bump,
+    )]
+    pub user_vault: Box<Account<'info, UserVault>>,
+}
@@ -1,11 +1,178 @@
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+
+use crate::errors::MovieReviewSystemError;
+
+#[derive(InitSpace)]
+#[account]
+pub struct ProgramConfig {
+    pub bump: u8,
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+
+    /// Reward per review in whole AST; scaled by the mint's decimals at payout.
+    pub reward_per_review: u64,
+    pub cooldown_seconds: i64,
+    pub vesting: VestingSchedule,
+    pub reward_mode: RewardMode,
+
+    /// AST mint created by `initialize_reward_mint`.
+    pub reward_mint: Option<Pubkey>,
+
+    /// Granularity of `rating_bps`; ratings must be a multiple of it.
+    pub rating_step_bps: u16,
+
+    pub edit_rules: ReviewEditRules,
+}
+
+impl ProgramConfig {
+    pub const DEFAULT_REWARD_PER_REVIEW: u64 = 5000;
+    pub const DEFAULT_COOLDOWN_SECONDS: i64 = 300;
+    /// Whole steps, matching the legacy 1 to 10 scale.
+    pub const DEFAULT_RATING_STEP_BPS: u16 = 100;
+
+    pub fn is_valid_rating_step(step: u16) -> bool {
+        step > 0 && MovieReview::MAX_RATING_BPS.is_multiple_of(step)
+    }
+
+    pub fn is_valid_rating_bps(&self, rating_bps: u16) -> bool {
+        rating_bps <= MovieReview::MAX_RATING_BPS && rating_bps.is_multiple_of(self.rating_step_bps.max(1))
+    }
+
+    /// Reward per review in base units for a mint with `decimals`.
+    pub fn reward_amount(&self, decimals: u8) -> Option<u64> {
+        10u64
+            .checked_pow(decimals as u32)
+            .and_then(|scale| self.reward_per_review.checked_mul(scale))
+    }
+
+    /// Lot for a reward of `amount` paid at `paid_at` for `receipt`, under the current schedule.
+    pub fn new_lot(&self, receipt: Pubkey, amount: u64, paid_at: i64) -> RewardLot {
+        let (cliff_at, end_at) = match self.vesting {
+            VestingSchedule::Immediate => (paid_at, paid_at),
+            VestingSchedule::Cliff => {
+                let vests_at = paid_at.saturating_add(self.cooldown_seconds);
+                (vests_at, vests_at)
+            }
+            VestingSchedule::Linear { cliff_seconds, duration_seconds } => (
+                paid_at.saturating_add(cliff_seconds),
+                paid_at.saturating_add(duration_seconds),
+            ),
+        };
+        RewardLot {
+            receipt,
+            amount,
+            released: 0,
+            start_at: paid_at,
+            cliff_at,
+            end_at,
+        }
+    }
+
+    /// The admin implicitly holds every role; anyone else needs a matching grant.
+    pub fn authorizes(&self, signer: Pubkey, grant: Option<&RoleGrant>, role: Role) -> bool {
+        if signer == self.admin {
+            return true;
+        }
+        grant.is_some_and(|grant| grant.member == signer && grant.role == role)
+    }
+}
+
+/// Limits on `update_review` and `set_aspect_ratings`; a zero field disables that rule.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct ReviewEditRules {
+    pub min_edit_interval_seconds: i64,
+    pub max_edits: u32,
+    /// The rating, including aspect ratings, is frozen this long after creation.
+    pub rating_lock_seconds: i64,
+}
+
+impl ReviewEditRules {
+    pub fn is_valid(&self) -> bool {
+        self.min_edit_interval_seconds >= 0 && self.rating_lock_seconds >= 0
+    }
+
+    /// Checks an edit of `review` at `now`; `rating_changed` is whether it touches the rating.
+    pub fn check_edit(&self, review: &MovieReview, now: i64, rating_changed: bool) -> Result<()> {
+        if self.min_edit_interval_seconds > 0
+            && now < review.updated_at.saturating_add(self.min_edit_interval_seconds)
+        {
+            return Err(MovieReviewSystemError::EditTooSoon.into());
+        }
+        if self.max_edits > 0 && review.revision >= self.max_edits {
+            return Err(MovieReviewSystemError::EditLimitReached.into());
+        }
+        if rating_changed
+            && self.rating_lock_seconds > 0
+            && now >= review.created_at.saturating_add(self.rating_lock_seconds)
+        {
+            return Err(MovieReviewSystemError::RatingLocked.into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum RewardMode {
+    /// Every eligible review is paid `reward_per_review` on creation.
+    Flat,
+    /// Every eligible review earns a point in the current emission epoch, and the epoch's
+    /// pool (in whole AST) is split pro rata by points once the epoch is finalized.
+    EpochPool { pool_per_epoch: u64 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum VestingSchedule {
+    /// Rewards are withdrawable as soon as they are paid.
+    Immediate,
+    /// Each reward unlocks `cooldown_seconds` after it was paid.
+    Cliff,
+    /// Each reward vests linearly over `duration_seconds` from payment, with nothing
+    /// withdrawable before `cliff_seconds`.
+    Linear {
+        cliff_seconds: i64,
+        duration_seconds: i64,
+    },
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            VestingSchedule::Immediate | VestingSchedule::Cliff => true,
+            VestingSchedule::Linear { cliff_seconds, duration_seconds } => {
+                cliff_seconds >= 0 && duration_seconds > 0 && cliff_seconds <= duration_seconds
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum Role {
+    /// Can add and edit movies.
+    Curator,
+    /// Can act on reviews.
+    Moderator,
+}
+
+#[derive(InitSpace)]
+#[account]
+pub struct RoleGrant {
+    pub bump: u8,
+    pub member: Pubkey,
+    pub role: Role,
+    pub granted_by: Pubkey,
+}
 
 #[derive(InitSpace)]
 #[account]
 pub struct MovieAccount {
     pub release_year: u16,
     pub bump: u8,
+    /// Disambiguates movies sharing a normalized title and release year.
+    pub edition: u8,
+    /// PDA seed, fixed at creation so later edits keep the address stable.
+    pub seed_hash: [u8; 32],
 
     #[max_len(100)]
     pub movie: String,
@@ -16,6 +183,145 @@ pub struct MovieAccount {
 
 }
 
+impl MovieAccount {
+    pub const MAX_FIELD_LEN: usize = 100;
+
+    /// Exact account size (without discriminator) for the given string fields.
+    pub fn space(movie: &str, director: &str, hero: &str) -> usize {
+        Self::INIT_SPACE - 3 * Self::MAX_FIELD_LEN + movie.len() + director.len() + hero.len()
+    }
+
+    /// Seed for the movie PDA: hash of the normalized title, release year and edition.
+    ///
+    /// Titles are compared case-insensitively with surrounding and repeated whitespace
+    /// collapsed, so a remake is told apart by its release year, and two releases of the
+    /// same title in the same year by `edition`.
+    pub fn seed_hash(movie: &str, release_year: u16, edition: u8) -> [u8; 32] {
+        let normalized = movie.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        hashv(&[normalized.as_bytes(), &release_year.to_le_bytes(), &[edition]]).to_bytes()
+    }
+}
+
+/// Per-movie rating aggregates, kept in step with the movie's reviews.
+#[derive(InitSpace)]
+#[account]
+pub struct MovieStats {
+    pub bump: u8,
+    pub movie: Pubkey,
+    pub review_count: u32,
+    pub rating_sum: u64,
+    /// `rating_histogram[i]` counts reviews rated `i + 1`.
+    pub rating_histogram: [u32; 10],
+    /// Sum of `MovieReview::rating_bps` over the counted reviews.
+    pub rating_bps_sum: u64,
+    /// Reviews that carry aspect ratings; the divisor for `aspect_sums`.
+    pub aspect_review_count: u32,
+    /// Sums in `AspectRatings::as_array` order.
+    pub aspect_sums: [u64; AspectRatings::COUNT],
+}
+
+impl MovieStats {
+    /// `rating_sum` and the histogram stay on the 1 to 10 scale via `MovieReview::bps_to_legacy`.
+    pub fn add_rating(&mut self, rating_bps: u16) -> Result<()> {
+        let rating = MovieReview::bps_to_legacy(rating_bps);
+        let bucket = &mut self.rating_histogram[rating as usize - 1];
+        *bucket = bucket.checked_add(1).ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.review_count = self
+            .review_count
+            .checked_add(1)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.rating_sum = self
+            .rating_sum
+            .checked_add(rating as u64)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.rating_bps_sum = self
+            .rating_bps_sum
+            .checked_add(rating_bps as u64)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn remove_rating(&mut self, rating_bps: u16) -> Result<()> {
+        let rating = MovieReview::bps_to_legacy(rating_bps);
+        let bucket = &mut self.rating_histogram[rating as usize - 1];
+        *bucket = bucket.checked_sub(1).ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.review_count = self
+            .review_count
+            .checked_sub(1)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.rating_sum = self
+            .rating_sum
+            .checked_sub(rating as u64)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.rating_bps_sum = self
+            .rating_bps_sum
+            .checked_sub(rating_bps as u64)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn add_aspects(&mut self, aspects: &AspectRatings) -> Result<()> {
+        for (sum, score) in self.aspect_sums.iter_mut().zip(aspects.as_array()) {
+            *sum = sum.checked_add(score as u64).ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        }
+        self.aspect_review_count = self
+            .aspect_review_count
+            .checked_add(1)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn remove_aspects(&mut self, aspects: &AspectRatings) -> Result<()> {
+        for (sum, score) in self.aspect_sums.iter_mut().zip(aspects.as_array()) {
+            *sum = sum.checked_sub(score as u64).ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        }
+        self.aspect_review_count = self
+            .aspect_review_count
+            .checked_sub(1)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Adds everything `review` contributes: the overall rating and any aspect ratings.
+    pub fn add_review(&mut self, review: &MovieReview) -> Result<()> {
+        self.add_rating(review.rating_bps)?;
+        if let Some(aspects) = &review.aspect_ratings {
+            self.add_aspects(aspects)?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_review(&mut self, review: &MovieReview) -> Result<()> {
+        self.remove_rating(review.rating_bps)?;
+        if let Some(aspects) = &review.aspect_ratings {
+            self.remove_aspects(aspects)?;
+        }
+        Ok(())
+    }
+}
+
+/// Optional per-aspect scores, each 1 to 10 like `movie_rating`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct AspectRatings {
+    pub story: u8,
+    pub acting: u8,
+    pub direction: u8,
+    pub music: u8,
+    pub visuals: u8,
+}
+
+impl AspectRatings {
+    pub const COUNT: usize = 5;
+
+    pub fn as_array(&self) -> [u8; Self::COUNT] {
+        [self.story, self.acting, self.direction, self.music, self.visuals]
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.as_array().iter().all(|score| (1..=10).contains(score))
+    }
+}
+
 #[derive(InitSpace)]
 #[account]
 pub struct MovieReview {
@@ -30,6 +336,481 @@ pub struct MovieReview {
 
     pub bump: u8,
     pub reviewer: Pubkey,
+    /// False when this reviewer was already rewarded for the movie by an earlier review.
+    pub reward_eligible: bool,
+    pub helpful_count: u32,
+    pub unhelpful_count: u32,
+    pub visibility: ReviewVisibility,
+    pub flag_count: u32,
+    pub aspect_ratings: Option<AspectRatings>,
+
+    /// Rating on a 0 to 1000 scale, where 1000 is 10.0. Reviews from the first deployment
+    /// are brought over by `migrate_review`.
+    pub rating_bps: u16,
+
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Number of edits through `update_review` or `set_aspect_ratings`; `0` means never edited.
+    pub revision: u32,
+
+    pub spoiler: bool,
+    /// Set by a moderator; keeps `spoiler` on whatever the author sends.
+    pub spoiler_forced: bool,
+    /// `ContentWarnings` bits.
+    pub content_warnings: u16,
+
+    /// `RewardReceipt::review_generation` at creation; tells this review apart from earlier,
+    /// deleted ones at the same address.
+    pub generation: u32,
+}
+
+/// Author-supplied display labels for a review.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReviewLabels {
+    pub spoiler: bool,
+    pub content_warnings: u16,
+}
+
+/// Bits of `MovieReview::content_warnings`.
+pub struct ContentWarnings;
+
+impl ContentWarnings {
+    pub const VIOLENCE: u16 = 1 << 0;
+    pub const SEXUAL_CONTENT: u16 = 1 << 1;
+    pub const STRONG_LANGUAGE: u16 = 1 << 2;
+    pub const SUBSTANCE_USE: u16 = 1 << 3;
+    pub const SELF_HARM: u16 = 1 << 4;
+    pub const FLASHING_LIGHTS: u16 = 1 << 5;
+    pub const ALL: u16 = Self::VIOLENCE
+        | Self::SEXUAL_CONTENT
+        | Self::STRONG_LANGUAGE
+        | Self::SUBSTANCE_USE
+        | Self::SELF_HARM
+        | Self::FLASHING_LIGHTS;
+
+    pub fn is_valid(bits: u16) -> bool {
+        bits & !Self::ALL == 0
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ReviewVisibility {
+    Visible,
+    /// Hidden by a moderator; the rating is left out of `MovieStats`.
+    Hidden,
+}
+
+impl MovieReview {
+    pub const MAX_RATING_BPS: u16 = 1000;
+
+    pub fn legacy_to_bps(movie_rating: u8) -> u16 {
+        movie_rating as u16 * 100
+    }
+
+    /// Nearest whole rating, kept within 1 to 10 so it always lands in a histogram bucket.
+    pub fn bps_to_legacy(rating_bps: u16) -> u8 {
+        (rating_bps.saturating_add(50) / 100).clamp(1, 10) as u8
+    }
+
+    /// Writes both scales.
+    pub fn set_rating_bps(&mut self, rating_bps: u16) {
+        self.rating_bps = rating_bps;
+        self.movie_rating = Self::bps_to_legacy(rating_bps);
+    }
+
+    /// Snapshot of the current ratings and comment, for `ReviewHistory`.
+    pub fn revision_entry(&self) -> ReviewRevision {
+        ReviewRevision {
+            revision: self.revision,
+            rating_bps: self.rating_bps,
+            comment_hash: hash(self.review_comment.as_bytes()).to_bytes(),
+            written_at: self.updated_at,
+            aspect_ratings: self.aspect_ratings,
+        }
+    }
+
+    pub fn set_labels(&mut self, labels: ReviewLabels) {
+        self.spoiler = labels.spoiler || self.spoiler_forced;
+        self.content_warnings = labels.content_warnings;
+    }
+
+    pub fn labels(&self) -> ReviewLabels {
+        ReviewLabels {
+            spoiler: self.spoiler,
+            content_warnings: self.content_warnings,
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.visibility == ReviewVisibility::Hidden
+    }
+
+    pub fn add_vote(&mut self, helpful: bool) -> Result<()> {
+        let count = if helpful { &mut self.helpful_count } else { &mut self.unhelpful_count };
+        *count = count.checked_add(1).ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn remove_vote(&mut self, helpful: bool) {
+        let count = if helpful { &mut self.helpful_count } else { &mut self.unhelpful_count };
+        *count = count.saturating_sub(1);
+    }
+}
+
+/// Reply counter for one review; kept apart so `MovieReview` keeps its layout.
+#[derive(InitSpace)]
+#[account]
+pub struct ReviewThread {
+    pub bump: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    /// Next reply index; never decremented within a generation, so reply addresses are not reused.
+    pub reply_count: u64,
+    /// `MovieReview::generation` the count belongs to; a thread left behind by a deleted
+    /// review starts over for a newer one at the same address.
+    pub review_generation: u32,
+}
+
+impl ReviewThread {
+    pub fn belongs_to(&self, review: &MovieReview) -> bool {
+        self.is_initialized && self.review_generation == review.generation
+    }
+
+    /// Index the next reply to `review` gets, counting a stale thread as empty.
+    pub fn next_reply_index(&self, review: &MovieReview) -> u64 {
+        if self.belongs_to(review) { self.reply_count } else { 0 }
+    }
+}
+
+#[derive(InitSpace)]
+#[account]
+pub struct ReviewReply {
+    pub bump: u8,
+    pub review: Pubkey,
+    /// `MovieReview::generation` replied to; part of the reply's seeds. Replies to a deleted
+    /// review outlive it and must not be shown under a newer one at the same address.
+    pub review_generation: u32,
+    pub index: u64,
+    pub author: Pubkey,
+    /// `None` for a top-level reply to the review.
+    pub parent_reply: Option<Pubkey>,
+    pub depth: u8,
+    #[max_len(200)]
+    pub content: String,
+}
+
+impl ReviewReply {
+    pub const MAX_CONTENT_LEN: usize = 200;
+    /// Depth of a top-level reply is 0.
+    pub const MAX_DEPTH: u8 = 4;
+
+    /// Whether this reply belongs to `review` rather than to an earlier, deleted one.
+    pub fn belongs_to(&self, review: &MovieReview) -> bool {
+        self.review_generation == review.generation
+    }
+}
+
+pub const MAX_REVIEW_HISTORY: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ReviewRevision {
+    pub revision: u32,
+    pub rating_bps: u16,
+    pub comment_hash: [u8; 32],
+    pub written_at: i64,
+    pub aspect_ratings: Option<AspectRatings>,
+}
+
+/// Ring buffer of a review's superseded revisions, oldest first from `head`.
+#[derive(InitSpace)]
+#[account]
+pub struct ReviewHistory {
+    pub bump: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub entries: [ReviewRevision; MAX_REVIEW_HISTORY],
+    pub head: u8,
+    pub len: u8,
+    /// `MovieReview::generation` the entries belong to; a history left behind by a deleted
+    /// review is reset before a newer one at the same address writes to it.
+    pub review_generation: u32,
+}
+
+impl ReviewHistory {
+    pub fn belongs_to(&self, review: &MovieReview) -> bool {
+        self.is_initialized && self.review_generation == review.generation
+    }
+
+    /// Drops every entry and binds the buffer to `generation`.
+    pub fn reset(&mut self, generation: u32) {
+        self.entries = [ReviewRevision::default(); MAX_REVIEW_HISTORY];
+        self.head = 0;
+        self.len = 0;
+        self.review_generation = generation;
+    }
+
+    /// Appends `entry`, overwriting the oldest one once the buffer is full.
+    pub fn push(&mut self, entry: ReviewRevision) {
+        let len = self.len as usize;
+        if len == MAX_REVIEW_HISTORY {
+            self.entries[self.head as usize] = entry;
+            self.head = ((self.head as usize + 1) % MAX_REVIEW_HISTORY) as u8;
+        } else {
+            self.entries[(self.head as usize + len) % MAX_REVIEW_HISTORY] = entry;
+            self.len += 1;
+        }
+    }
+}
+
+/// One flag per (review, flagger), so a user cannot pile flags onto a review.
+#[derive(InitSpace)]
+#[account]
+pub struct ReviewFlag {
+    pub bump: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub flagger: Pubkey,
+    pub reason_code: u8,
+    pub flagged_at: i64,
+    /// `MovieReview::generation` of the flagged review; a flag left over from a deleted
+    /// review does not stop the flagger from flagging a newer one at the same address.
+    pub review_generation: u32,
+}
+
+impl ReviewFlag {
+    /// Whether this flag is counted in `review.flag_count`.
+    pub fn counts_for(&self, review: &MovieReview) -> bool {
+        self.is_initialized && self.review_generation == review.generation
+    }
+}
+
+/// One voter's helpfulness vote on one review.
+#[derive(InitSpace)]
+#[account]
+pub struct ReviewVote {
+    pub bump: u8,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub voter: Pubkey,
+    pub helpful: bool,
+    /// `MovieReview::generation` of the review voted on; a vote left over from a deleted
+    /// review counts for nothing on a newer one at the same address.
+    pub review_generation: u32,
+}
+
+impl ReviewVote {
+    /// Whether this vote is counted in `review`'s tallies.
+    pub fn counts_for(&self, review: &MovieReview) -> bool {
+        self.is_initialized && self.review_generation == review.generation
+    }
+}
+
+/// Global reward budget. Rewards are paid out of the `treasury` token account, which
+/// this PDA owns, and never minted on demand.
+#[derive(InitSpace)]
+#[account]
+pub struct EmissionState {
+    pub bump: u8,
+    pub treasury_bump: u8,
+    pub mint: Pubkey,
+    pub treasury: Pubkey,
+    pub lifetime_cap: u64,
+    pub lifetime_emitted: u64,
+    pub epoch_cap: u64,
+    pub epoch_duration_seconds: i64,
+    pub epoch_started_at: i64,
+    pub epoch_emitted: u64,
+    pub epoch_index: u64,
+    /// Finalized epoch pools that have not been claimed yet; still held by the treasury.
+    pub reserved: u64,
+}
+
+impl EmissionState {
+    fn epochs_elapsed(&self, now: i64) -> i64 {
+        now.saturating_sub(self.epoch_started_at) / self.epoch_duration_seconds
+    }
+
+    /// Index of the epoch `now` falls in, whether or not it has been rolled yet.
+    pub fn current_epoch_index(&self, now: i64) -> u64 {
+        self.epoch_index + self.epochs_elapsed(now) as u64
+    }
+
+    /// Starts a new emission epoch once the current one has run its course.
+    pub fn roll_epoch(&mut self, now: i64) {
+        let epochs = self.epochs_elapsed(now);
+        if epochs > 0 {
+            self.epoch_started_at += epochs * self.epoch_duration_seconds;
+            self.epoch_emitted = 0;
+            self.epoch_index += epochs as u64;
+        }
+    }
+
+    /// Treasury tokens not already promised to finalized epochs.
+    pub fn available(&self, treasury_balance: u64) -> u64 {
+        treasury_balance.saturating_sub(self.reserved)
+    }
+
+    /// Whether `amount` fits both caps and the unreserved tokens left in the treasury.
+    pub fn can_emit(&self, amount: u64, treasury_balance: u64) -> bool {
+        amount <= self.available(treasury_balance)
+            && self
+                .lifetime_emitted
+                .checked_add(amount)
+                .is_some_and(|total| total <= self.lifetime_cap)
+            && self
+                .epoch_emitted
+                .checked_add(amount)
+                .is_some_and(|total| total <= self.epoch_cap)
+    }
+
+    pub fn record_emission(&mut self, amount: u64) -> Result<()> {
+        self.lifetime_emitted = self
+            .lifetime_emitted
+            .checked_add(amount)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.epoch_emitted = self
+            .epoch_emitted
+            .checked_add(amount)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Returns budget for part of a finalized pool that will never be paid out.
+    pub fn release_reserved(&mut self, amount: u64) {
+        self.reserved = self.reserved.saturating_sub(amount);
+        self.lifetime_emitted = self.lifetime_emitted.saturating_sub(amount);
+    }
+
+    /// Returns budget for tokens sent back to the treasury. `paid_at` decides whether the
+    /// current epoch's budget is credited too.
+    pub fn record_return(&mut self, amount: u64, paid_at: Option<i64>) {
+        self.lifetime_emitted = self.lifetime_emitted.saturating_sub(amount);
+        if paid_at.is_some_and(|paid_at| paid_at >= self.epoch_started_at) {
+            self.epoch_emitted = self.epoch_emitted.saturating_sub(amount);
+        }
+    }
+}
+
+/// One emission epoch's share of review points under `RewardMode::EpochPool`.
+#[derive(InitSpace)]
+#[account]
+pub struct Epoch {
+    pub bump: u8,
+    pub is_initialized: bool,
+    pub index: u64,
+    pub started_at: i64,
+    pub ends_at: i64,
+    pub total_points: u64,
+    pub finalized: bool,
+    /// Base units set aside for this epoch when it was finalized.
+    pub pool: u64,
+    /// Points settled so far, by a claim or by deleting the review that earned them.
+    pub claimed_points: u64,
+    /// Pool settled so far: paid out, forfeited, or released as rounding dust.
+    pub claimed_amount: u64,
+}
+
+impl Epoch {
+    /// Pro-rata share of the pool for `points`.
+    pub fn share(&self, points: u64) -> u64 {
+        if self.total_points == 0 {
+            return 0;
+        }
+        (self.pool as u128 * points as u128 / self.total_points as u128) as u64
+    }
+
+    /// Records `points` settled for `amount`. Once every point is settled, returns the
+    /// rounding dust left in the pool, which the caller releases back to the budget.
+    pub fn settle(&mut self, points: u64, amount: u64) -> Result<u64> {
+        self.claimed_points = self
+            .claimed_points
+            .checked_add(points)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        self.claimed_amount = self
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        if self.claimed_points < self.total_points {
+            return Ok(0);
+        }
+        let dust = self.pool.saturating_sub(self.claimed_amount);
+        self.claimed_amount += dust;
+        Ok(dust)
+    }
+}
+
+#[derive(InitSpace)]
+#[account]
+pub struct EpochParticipation {
+    pub bump: u8,
+    pub is_initialized: bool,
+    pub epoch_index: u64,
+    pub reviewer: Pubkey,
+    pub points: u64,
+}
+
+/// Outlives the review it was created for, so a movie pays each reviewer at most once.
+#[derive(InitSpace)]
+#[account]
+pub struct RewardReceipt {
+    pub bump: u8,
+    pub is_initialized: bool,
+    pub movie: Pubkey,
+    pub reviewer: Pubkey,
+    pub amount: u64,
+    pub rewarded_at: i64,
+    pub clawed_back: u64,
+    /// Epoch the review earned a point in, under `RewardMode::EpochPool`.
+    pub epoch_index: Option<u64>,
+    /// Vesting schedule of the paid lot, so a clawback is unaffected by later config changes.
+    pub cliff_at: i64,
+    pub end_at: i64,
+    /// Reviews created at this movie and reviewer's review address so far.
+    pub review_generation: u32,
+}
+
+impl RewardReceipt {
+    /// The reward's lot as it was paid, before any release or clawback. `key` is this receipt's address.
+    pub fn lot(&self, key: Pubkey) -> RewardLot {
+        RewardLot {
+            receipt: key,
+            amount: self.amount,
+            released: 0,
+            start_at: self.rewarded_at,
+            cliff_at: self.cliff_at,
+            end_at: self.end_at,
+        }
+    }
+}
+
+pub const MAX_REWARD_LOTS: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct RewardLot {
+    /// Account the lot was paid for: a `RewardReceipt`, an `Epoch` for claims, or the vault
+    /// itself for a migrated legacy balance. The default key marks a folded lot.
+    pub receipt: Pubkey,
+    pub amount: u64,
+    /// Portion of `amount` already moved to `withdrawable_amount`.
+    pub released: u64,
+    pub start_at: i64,
+    pub cliff_at: i64,
+    pub end_at: i64,
+}
+
+impl RewardLot {
+    /// Amount vested by `now`: nothing before the cliff, then linear up to `end_at`.
+    pub fn vested(&self, now: i64) -> u64 {
+        if now < self.cliff_at {
+            return 0;
+        }
+        if now >= self.end_at || self.end_at <= self.start_at {
+            return self.amount;
+        }
+        let elapsed = (now - self.start_at) as u128;
+        let duration = (self.end_at - self.start_at) as u128;
+        (self.amount as u128 * elapsed / duration) as u64
+    }
 }
 
 #[derive(InitSpace)]
@@ -37,8 +818,745 @@ pub struct MovieReview {
 pub struct UserVault {
     pub bump: u8,
     pub user: Pubkey,
+    pub is_initialized: bool,
+    /// Vested rewards released from `lots` but not yet withdrawn.
+    pub withdrawable_amount: u64,
+    /// Ring buffer of rewards that are not fully released; the oldest lot sits at `lot_head`.
+    pub lots: [RewardLot; MAX_REWARD_LOTS],
+    pub lot_head: u8,
+    pub lot_count: u8,
+    /// Optional delegate that withdraws instead of `user`.
+    pub withdraw_authority: Option<Pubkey>,
+    /// Rewards paid before this went to an earlier, closed vault; `0` for migrated vaults.
+    pub created_at: i64,
+}
+
+impl UserVault {
+    /// The key allowed to withdraw: the delegate when set, otherwise the owner.
+    pub fn withdraw_signer(&self) -> Pubkey {
+        self.withdraw_authority.unwrap_or(self.user)
+    }
+
+    fn lot_index(&self, offset: usize) -> usize {
+        (self.lot_head as usize + offset) % MAX_REWARD_LOTS
+    }
+
+    /// Appends a lot. When the buffer is full the reward is folded into the newest lot,
+    /// which then follows the later of the two schedules and no longer belongs to a single
+    /// receipt. Whatever the newest lot already released is in `withdrawable_amount`, so it
+    /// is dropped from the lot before folding; otherwise the later schedule would report
+    /// less vested than was released.
+    pub fn push_lot(&mut self, lot: RewardLot) -> Result<()> {
+        let count = self.lot_count as usize;
+        if count == MAX_REWARD_LOTS {
+            let newest = &mut self.lots[self.lot_index(count - 1)];
+            newest.receipt = Pubkey::default();
+            newest.amount -= newest.released;
+            newest.released = 0;
+            newest.amount = newest
+                .amount
+                .checked_add(lot.amount)
+                .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+            newest.start_at = newest.start_at.max(lot.start_at);
+            newest.cliff_at = newest.cliff_at.max(lot.cliff_at);
+            newest.end_at = newest.end_at.max(lot.end_at);
+            return Ok(());
+        }
+
+        let index = self.lot_index(count);
+        self.lots[index] = lot;
+        self.lot_count += 1;
+        Ok(())
+    }
+
+    /// Moves everything vested by `now` into `withdrawable_amount` and drops fully
+    /// released lots. Returns the amount released.
+    pub fn release_vested(&mut self, now: i64) -> Result<u64> {
+        let mut released: u64 = 0;
+        let mut kept = 0;
+        for offset in 0..self.lot_count as usize {
+            let mut lot = self.lots[self.lot_index(offset)];
+            let vested = lot.vested(now);
+            released = released
+                .checked_add(vested.saturating_sub(lot.released))
+                .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+            // A clawback can leave `vested` briefly below what was already released.
+            lot.released = lot.released.max(vested);
+
+            if lot.released < lot.amount {
+                let index = self.lot_index(kept);
+                self.lots[index] = lot;
+                kept += 1;
+            }
+        }
+        for offset in kept..self.lot_count as usize {
+            let index = self.lot_index(offset);
+            self.lots[index] = RewardLot::default();
+        }
+        self.lot_count = kept as u8;
+
+        self.withdrawable_amount = self
+            .withdrawable_amount
+            .checked_add(released)
+            .ok_or(MovieReviewSystemError::ArithmeticOverflow)?;
+        Ok(released)
+    }
+
+    /// Removes up to `amount` of unreleased rewards from the lot paid for `receipt`, or,
+    /// if that lot was folded away, from the newest folded lot. Returns the amount removed.
+    pub fn claw_back(&mut self, receipt: Pubkey, amount: u64) -> u64 {
+        let count = self.lot_count as usize;
+        let find = |key: Pubkey| (0..count).rev().find(|&offset| self.lots[self.lot_index(offset)].receipt == key);
+        let Some(offset) = find(receipt).or_else(|| find(Pubkey::default())) else {
+            return 0;
+        };
+
+        let lot = &mut self.lots[self.lot_index(offset)];
+        let taken = (lot.amount - lot.released).min(amount);
+        lot.amount -= taken;
+        if lot.amount == lot.released {
+            for later in offset + 1..count {
+                self.lots[self.lot_index(later - 1)] = self.lots[self.lot_index(later)];
+            }
+            let last = self.lot_index(count - 1);
+            self.lots[last] = RewardLot::default();
+            self.lot_count -= 1;
+        }
+        taken
+    }
+
+    /// Unreleased amount still held in the buffer.
+    pub fn pending_amount(&self) -> u64 {
+        (0..self.lot_count as usize)
+            .map(|offset| {
+                let lot = self.lots[self.lot_index(offset)];
+                lot.amount - lot.released
+            })
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Totals as of `now`, without releasing anything.
+    pub fn summary(&self, now: i64) -> VaultSummary {
+        let mut summary = VaultSummary {
+            pending: 0,
+            withdrawable: self.withdrawable_amount,
+            fully_vested_at: None,
+        };
+        for offset in 0..self.lot_count as usize {
+            let lot = self.lots[self.lot_index(offset)];
+            let vested = lot.vested(now);
+            summary.withdrawable = summary.withdrawable.saturating_add(vested.saturating_sub(lot.released));
+            if vested < lot.amount {
+                summary.pending = summary.pending.saturating_add(lot.amount - vested);
+                summary.fully_vested_at = Some(summary.fully_vested_at.map_or(lot.end_at, |at| at.max(lot.end_at)));
+            }
+        }
+        summary
+    }
+}
+
+/// `UserVault` layout of the first deployment, before reward lots; read by `migrate_user_vault`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyUserVault {
+    pub bump: u8,
+    pub user: Pubkey,
+    /// Rewards still in the cooldown.
     pub balance: u64,
     pub is_initialized: bool,
     pub last_withdraw_timestamp: i64,
     pub withdrawable_amount: u64,
-}
\ No newline at end of file
+}
+
+impl LegacyUserVault {
+    /// Serialized size, without discriminator.
+    pub const SIZE: usize = 1 + 32 + 8 + 1 + 8 + 8;
+    /// `balance` became withdrawable this long after `last_withdraw_timestamp`.
+    pub const COOLDOWN_SECONDS: i64 = 300;
+
+    /// The same vault in the current layout, with `balance` as a lot that vests when the
+    /// legacy cooldown would have released it. `key` is the vault's address.
+    pub fn migrate(&self, key: Pubkey) -> UserVault {
+        let mut lots = [RewardLot::default(); MAX_REWARD_LOTS];
+        let mut lot_count = 0;
+        if self.balance > 0 {
+            let vests_at = self.last_withdraw_timestamp.saturating_add(Self::COOLDOWN_SECONDS);
+            lots[0] = RewardLot {
+                receipt: key,
+                amount: self.balance,
+                released: 0,
+                start_at: self.last_withdraw_timestamp,
+                cliff_at: vests_at,
+                end_at: vests_at,
+            };
+            lot_count = 1;
+        }
+        UserVault {
+            bump: self.bump,
+            user: self.user,
+            is_initialized: self.is_initialized,
+            withdrawable_amount: self.withdrawable_amount,
+            lots,
+            lot_head: 0,
+            lot_count,
+            withdraw_authority: None,
+            created_at: 0,
+        }
+    }
+}
+
+/// `MovieAccount` layout of the first deployment, at `[b"movie", title]`; read by `migrate_movie`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyMovieAccount {
+    pub release_year: u16,
+    pub bump: u8,
+    pub movie: String,
+    pub director: String,
+    pub hero: String,
+}
+
+impl LegacyMovieAccount {
+    /// Reads `info` if it is a movie from the first deployment, checked by its address.
+    pub fn read(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        let legacy = (info.owner == &crate::ID && data.starts_with(MovieAccount::DISCRIMINATOR))
+            .then(|| Self::deserialize(&mut &data[MovieAccount::DISCRIMINATOR.len()..]).ok())
+            .flatten()
+            .filter(|legacy| {
+                Pubkey::create_program_address(&[b"movie", legacy.movie.as_bytes(), &[legacy.bump]], &crate::ID)
+                    .is_ok_and(|address| address == info.key())
+            });
+        legacy.ok_or_else(|| MovieReviewSystemError::AlreadyMigrated.into())
+    }
+}
+
+/// `MovieReview` layout of the first deployment; read by `migrate_review`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyMovieReview {
+    pub movie_rating: u8,
+    pub movie_address: Pubkey,
+    pub review_comment: String,
+    pub reviewer_name: String,
+    pub bump: u8,
+    pub reviewer: Pubkey,
+}
+
+impl LegacyMovieReview {
+    /// Allocated size, without discriminator.
+    pub const SPACE: usize = 1 + 32 + (4 + 200) + (4 + 50) + 1 + 32;
+
+    /// Reads `info` if it is a review from the first deployment, told apart by its size.
+    pub fn read(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        let legacy = (info.owner == &crate::ID
+            && data.len() == MovieReview::DISCRIMINATOR.len() + Self::SPACE
+            && data.starts_with(MovieReview::DISCRIMINATOR))
+        .then(|| Self::deserialize(&mut &data[MovieReview::DISCRIMINATOR.len()..]).ok())
+        .flatten();
+        legacy.ok_or_else(|| MovieReviewSystemError::AlreadyMigrated.into())
+    }
+}
+
+/// Return value of the `get_vault_summary` view instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VaultSummary {
+    pub pending: u64,
+    pub withdrawable: u64,
+    pub fully_vested_at: Option<i64>,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lot(amount: u64, start_at: i64, cliff_at: i64, end_at: i64) -> RewardLot {
+        RewardLot {
+            receipt: Pubkey::new_unique(),
+            amount,
+            released: 0,
+            start_at,
+            cliff_at,
+            end_at,
+        }
+    }
+
+    fn empty_vault() -> UserVault {
+        UserVault {
+            bump: 0,
+            user: Pubkey::default(),
+            is_initialized: true,
+            withdrawable_amount: 0,
+            lots: [RewardLot::default(); MAX_REWARD_LOTS],
+            lot_head: 0,
+            lot_count: 0,
+            withdraw_authority: None,
+            created_at: 0,
+        }
+    }
+
+    fn config(rating_step_bps: u16) -> ProgramConfig {
+        ProgramConfig {
+            bump: 0,
+            admin: Pubkey::new_unique(),
+            pending_admin: None,
+            reward_per_review: ProgramConfig::DEFAULT_REWARD_PER_REVIEW,
+            cooldown_seconds: ProgramConfig::DEFAULT_COOLDOWN_SECONDS,
+            vesting: VestingSchedule::Immediate,
+            reward_mode: RewardMode::Flat,
+            reward_mint: None,
+            rating_step_bps,
+            edit_rules: ReviewEditRules::default(),
+        }
+    }
+
+    fn emission_state() -> EmissionState {
+        EmissionState {
+            bump: 0,
+            treasury_bump: 0,
+            mint: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            lifetime_cap: 1000,
+            lifetime_emitted: 0,
+            epoch_cap: 100,
+            epoch_duration_seconds: 100,
+            epoch_started_at: 1000,
+            epoch_emitted: 0,
+            epoch_index: 0,
+            reserved: 0,
+        }
+    }
+
+    fn review(generation: u32) -> MovieReview {
+        MovieReview {
+            movie_rating: 8,
+            movie_address: Pubkey::new_unique(),
+            review_comment: String::new(),
+            reviewer_name: String::new(),
+            bump: 0,
+            reviewer: Pubkey::new_unique(),
+            reward_eligible: true,
+            helpful_count: 0,
+            unhelpful_count: 0,
+            visibility: ReviewVisibility::Visible,
+            flag_count: 0,
+            aspect_ratings: None,
+            rating_bps: 800,
+            created_at: 1000,
+            updated_at: 1000,
+            revision: 0,
+            spoiler: false,
+            spoiler_forced: false,
+            content_warnings: 0,
+            generation,
+        }
+    }
+
+    #[test]
+    fn stale_thread_and_replies_do_not_carry_over() {
+        let old = review(1);
+        let new = review(2);
+        let thread = ReviewThread {
+            bump: 0,
+            is_initialized: true,
+            review: Pubkey::default(),
+            reply_count: 5,
+            review_generation: 1,
+        };
+        assert_eq!(thread.next_reply_index(&old), 5);
+        assert_eq!(thread.next_reply_index(&new), 0);
+
+        let reply = ReviewReply {
+            bump: 0,
+            review: Pubkey::default(),
+            review_generation: 1,
+            index: 0,
+            author: Pubkey::default(),
+            parent_reply: None,
+            depth: 0,
+            content: String::new(),
+        };
+        assert!(reply.belongs_to(&old));
+        assert!(!reply.belongs_to(&new));
+    }
+
+    #[test]
+    fn vested_is_zero_before_cliff() {
+        let lot = lot(1000, 0, 100, 1000);
+        assert_eq!(lot.vested(0), 0);
+        assert_eq!(lot.vested(99), 0);
+    }
+
+    #[test]
+    fn vested_is_linear_between_cliff_and_end() {
+        let lot = lot(1000, 0, 100, 1000);
+        assert_eq!(lot.vested(100), 100);
+        assert_eq!(lot.vested(500), 500);
+        assert_eq!(lot.vested(999), 999);
+    }
+
+    #[test]
+    fn vested_is_full_at_end_and_for_instant_lots() {
+        assert_eq!(lot(1000, 0, 100, 1000).vested(1000), 1000);
+        assert_eq!(lot(1000, 0, 100, 1000).vested(5000), 1000);
+        assert_eq!(lot(1000, 50, 50, 50).vested(50), 1000);
+    }
+
+    #[test]
+    fn push_appends_until_full() {
+        let mut vault = empty_vault();
+        for i in 0..MAX_REWARD_LOTS as i64 {
+            vault.push_lot(lot(10, i, i, i)).unwrap();
+        }
+        assert_eq!(vault.lot_count as usize, MAX_REWARD_LOTS);
+        assert_eq!(vault.pending_amount(), 10 * MAX_REWARD_LOTS as u64);
+    }
+
+    #[test]
+    fn push_folds_into_newest_lot_when_full() {
+        let mut vault = empty_vault();
+        for _ in 0..MAX_REWARD_LOTS {
+            vault.push_lot(lot(10, 0, 0, 100)).unwrap();
+        }
+        vault.push_lot(lot(5, 50, 60, 200)).unwrap();
+
+        assert_eq!(vault.lot_count as usize, MAX_REWARD_LOTS);
+        let newest = vault.lots[MAX_REWARD_LOTS - 1];
+        assert_eq!(newest.amount, 15);
+        assert_eq!((newest.start_at, newest.cliff_at, newest.end_at), (50, 60, 200));
+    }
+
+    #[test]
+    fn fold_into_partially_released_lot_does_not_underflow() {
+        let mut vault = empty_vault();
+        for _ in 0..MAX_REWARD_LOTS {
+            vault.push_lot(lot(100, 0, 0, 100)).unwrap();
+        }
+        assert_eq!(vault.release_vested(50).unwrap(), 50 * MAX_REWARD_LOTS as u64);
+
+        // The folded lot has a later cliff, so nothing of it has vested at 60.
+        vault.push_lot(lot(100, 60, 80, 160)).unwrap();
+        let newest = vault.lots[MAX_REWARD_LOTS - 1];
+        assert_eq!((newest.amount, newest.released), (150, 0));
+
+        assert_eq!(vault.summary(60).withdrawable, vault.withdrawable_amount + 15 * 10);
+        assert_eq!(vault.release_vested(60).unwrap(), 15 * 10);
+        assert_eq!(vault.release_vested(160).unwrap(), 15 * 40 + 150);
+        assert_eq!(vault.lot_count, 0);
+        assert_eq!(vault.withdrawable_amount, 100 * MAX_REWARD_LOTS as u64 + 100);
+    }
+
+    #[test]
+    fn claw_back_takes_from_the_receipts_own_lot() {
+        let mut vault = empty_vault();
+        let older = lot(100, 0, 0, 100);
+        vault.push_lot(older).unwrap();
+        vault.push_lot(lot(40, 0, 0, 100)).unwrap();
+        vault.push_lot(lot(70, 0, 0, 100)).unwrap();
+
+        assert_eq!(vault.claw_back(older.receipt, 60), 60);
+        assert_eq!(vault.lot_count, 3);
+        assert_eq!(vault.claw_back(older.receipt, 1000), 40);
+        assert_eq!(vault.lot_count, 2);
+        assert_eq!(vault.pending_amount(), 110);
+        assert_eq!(vault.lots[vault.lot_index(0)].amount, 40);
+        assert_eq!(vault.lots[vault.lot_index(1)].amount, 70);
+
+        assert_eq!(vault.claw_back(older.receipt, 10), 0);
+        assert_eq!(vault.claw_back(Pubkey::new_unique(), 10), 0);
+    }
+
+    #[test]
+    fn claw_back_falls_back_to_folded_lot() {
+        let mut vault = empty_vault();
+        for _ in 0..MAX_REWARD_LOTS {
+            vault.push_lot(lot(10, 0, 0, 100)).unwrap();
+        }
+        let folded = lot(5, 0, 0, 100);
+        vault.push_lot(folded).unwrap();
+
+        assert_eq!(vault.claw_back(folded.receipt, 5), 5);
+        let newest = vault.lots[vault.lot_index(MAX_REWARD_LOTS - 1)];
+        assert_eq!((newest.receipt, newest.amount), (Pubkey::default(), 10));
+    }
+
+    #[test]
+    fn claw_back_keeps_released_part() {
+        let mut vault = empty_vault();
+        let paid = lot(100, 0, 0, 100);
+        vault.push_lot(paid).unwrap();
+        vault.release_vested(50).unwrap();
+
+        assert_eq!(vault.claw_back(paid.receipt, 100), 50);
+        assert_eq!(vault.withdrawable_amount, 50);
+        assert_eq!(vault.lot_count, 0);
+    }
+
+    #[test]
+    fn release_after_partial_claw_back_does_not_underflow() {
+        let mut vault = empty_vault();
+        let paid = lot(100, 0, 0, 100);
+        vault.push_lot(paid).unwrap();
+        vault.release_vested(50).unwrap();
+
+        // 60 of 100 remains with 50 released, but only 30 of it has vested at 50.
+        assert_eq!(vault.claw_back(paid.receipt, 40), 40);
+        assert_eq!(vault.summary(50).withdrawable, 50);
+        assert_eq!(vault.release_vested(50).unwrap(), 0);
+        assert_eq!(vault.release_vested(100).unwrap(), 10);
+        assert_eq!(vault.withdrawable_amount, 60);
+        assert_eq!(vault.lot_count, 0);
+    }
+
+    #[test]
+    fn history_overwrites_oldest_and_resets() {
+        let mut history = ReviewHistory {
+            bump: 0,
+            is_initialized: true,
+            review: Pubkey::default(),
+            entries: [ReviewRevision::default(); MAX_REVIEW_HISTORY],
+            head: 0,
+            len: 0,
+            review_generation: 1,
+        };
+        for revision in 0..MAX_REVIEW_HISTORY as u32 + 2 {
+            history.push(ReviewRevision { revision, ..Default::default() });
+        }
+        assert_eq!(history.len as usize, MAX_REVIEW_HISTORY);
+        assert_eq!(history.entries[history.head as usize].revision, 2);
+
+        history.reset(2);
+        assert_eq!((history.head, history.len, history.review_generation), (0, 0, 2));
+        assert_eq!(history.entries[0].revision, 0);
+    }
+
+    #[test]
+    fn legacy_review_space_matches_a_full_review() {
+        let legacy = LegacyMovieReview {
+            movie_rating: 10,
+            movie_address: Pubkey::new_unique(),
+            review_comment: "c".repeat(200),
+            reviewer_name: "n".repeat(50),
+            bump: 255,
+            reviewer: Pubkey::new_unique(),
+        };
+        let mut bytes = Vec::new();
+        legacy.serialize(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), LegacyMovieReview::SPACE);
+        assert_ne!(LegacyMovieReview::SPACE, MovieReview::INIT_SPACE);
+    }
+
+    #[test]
+    fn legacy_vault_migrates_balance_into_a_cooldown_lot() {
+        let legacy = LegacyUserVault {
+            bump: 7,
+            user: Pubkey::new_unique(),
+            balance: 50,
+            is_initialized: true,
+            last_withdraw_timestamp: 1000,
+            withdrawable_amount: 20,
+        };
+        let mut bytes = Vec::new();
+        legacy.serialize(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), LegacyUserVault::SIZE);
+
+        let key = Pubkey::new_unique();
+        let mut vault = legacy.migrate(key);
+        assert_eq!((vault.bump, vault.user, vault.withdrawable_amount), (7, legacy.user, 20));
+        assert_eq!(vault.lot_count, 1);
+        assert_eq!(vault.lots[0].receipt, key);
+        assert_eq!(vault.release_vested(1299).unwrap(), 0);
+        assert_eq!(vault.release_vested(1300).unwrap(), 50);
+        assert_eq!(vault.withdrawable_amount, 70);
+    }
+
+    #[test]
+    fn release_drops_fully_released_lots_in_order() {
+        let mut vault = empty_vault();
+        vault.push_lot(lot(10, 0, 0, 0)).unwrap();
+        vault.push_lot(lot(20, 0, 100, 100)).unwrap();
+        vault.push_lot(lot(30, 0, 0, 0)).unwrap();
+
+        assert_eq!(vault.release_vested(0).unwrap(), 40);
+        assert_eq!(vault.lot_count, 1);
+        assert_eq!(vault.lots[vault.lot_index(0)].amount, 20);
+        assert_eq!(vault.summary(0).fully_vested_at, Some(100));
+    }
+
+    #[test]
+    fn settling_the_last_point_releases_rounding_dust() {
+        let mut epoch = Epoch {
+            bump: 0,
+            is_initialized: true,
+            index: 3,
+            started_at: 0,
+            ends_at: 100,
+            total_points: 3,
+            finalized: true,
+            pool: 100,
+            claimed_points: 0,
+            claimed_amount: 0,
+        };
+        let share = epoch.share(1);
+        assert_eq!(share, 33);
+
+        assert_eq!(epoch.settle(2, share * 2).unwrap(), 0);
+        // A forfeited point settles like a claimed one, so the dust still comes back.
+        assert_eq!(epoch.settle(1, share).unwrap(), 1);
+        assert_eq!((epoch.claimed_points, epoch.claimed_amount), (3, 100));
+    }
+
+    #[test]
+    fn bps_rounds_to_the_nearest_legacy_rating() {
+        assert_eq!(MovieReview::bps_to_legacy(0), 1);
+        assert_eq!(MovieReview::bps_to_legacy(50), 1);
+        assert_eq!(MovieReview::bps_to_legacy(949), 9);
+        assert_eq!(MovieReview::bps_to_legacy(950), 10);
+        assert_eq!(MovieReview::bps_to_legacy(1000), 10);
+        assert_eq!(MovieReview::bps_to_legacy(MovieReview::legacy_to_bps(7)), 7);
+    }
+
+    #[test]
+    fn rating_step_must_divide_the_scale() {
+        assert!(ProgramConfig::is_valid_rating_step(ProgramConfig::DEFAULT_RATING_STEP_BPS));
+        assert!(ProgramConfig::is_valid_rating_step(1));
+        assert!(ProgramConfig::is_valid_rating_step(250));
+        assert!(ProgramConfig::is_valid_rating_step(1000));
+        assert!(!ProgramConfig::is_valid_rating_step(0));
+        assert!(!ProgramConfig::is_valid_rating_step(300));
+        assert!(!ProgramConfig::is_valid_rating_step(2000));
+    }
+
+    #[test]
+    fn rating_bps_follows_the_configured_step() {
+        let quarters = config(250);
+        assert!(quarters.is_valid_rating_bps(0));
+        assert!(quarters.is_valid_rating_bps(750));
+        assert!(quarters.is_valid_rating_bps(1000));
+        assert!(!quarters.is_valid_rating_bps(800));
+        assert!(!quarters.is_valid_rating_bps(1250));
+
+        // A zero step, as on a config that predates it, accepts any rating on the scale.
+        let unset = config(0);
+        assert!(unset.is_valid_rating_bps(837));
+        assert!(!unset.is_valid_rating_bps(1001));
+    }
+
+    #[test]
+    fn seed_hash_normalizes_titles_but_not_year_or_edition() {
+        let hash = MovieAccount::seed_hash("The Thing", 1982, 0);
+        assert_eq!(MovieAccount::seed_hash("  the   THING ", 1982, 0), hash);
+        assert_eq!(MovieAccount::seed_hash("the\tthing", 1982, 0), hash);
+
+        // A remake gets its own address, as does a second release in the same year.
+        assert_ne!(MovieAccount::seed_hash("The Thing", 2011, 0), hash);
+        assert_ne!(MovieAccount::seed_hash("The Thing", 1982, 1), hash);
+        assert_ne!(MovieAccount::seed_hash("TheThing", 1982, 0), hash);
+    }
+
+    #[test]
+    fn edit_rules_apply_at_their_boundaries() {
+        let rules = ReviewEditRules {
+            min_edit_interval_seconds: 60,
+            max_edits: 2,
+            rating_lock_seconds: 600,
+        };
+        let mut review = review(1);
+
+        assert_eq!(rules.check_edit(&review, 1059, false), Err(MovieReviewSystemError::EditTooSoon.into()));
+        assert!(rules.check_edit(&review, 1060, false).is_ok());
+
+        review.revision = 1;
+        assert!(rules.check_edit(&review, 1060, false).is_ok());
+        review.revision = 2;
+        assert_eq!(rules.check_edit(&review, 1060, false), Err(MovieReviewSystemError::EditLimitReached.into()));
+
+        review.revision = 0;
+        assert!(rules.check_edit(&review, 1599, true).is_ok());
+        assert_eq!(rules.check_edit(&review, 1600, true), Err(MovieReviewSystemError::RatingLocked.into()));
+        // Only a rating change is locked.
+        assert!(rules.check_edit(&review, 1600, false).is_ok());
+    }
+
+    #[test]
+    fn zero_edit_rules_are_disabled() {
+        let mut review = review(1);
+        review.revision = u32::MAX;
+        let locked = ReviewEditRules {
+            min_edit_interval_seconds: 60,
+            max_edits: 2,
+            rating_lock_seconds: 600,
+        };
+
+        let rules = ReviewEditRules { min_edit_interval_seconds: 0, ..locked };
+        assert_eq!(rules.check_edit(&review, 1000, false), Err(MovieReviewSystemError::EditLimitReached.into()));
+
+        let rules = ReviewEditRules { max_edits: 0, ..locked };
+        assert!(rules.check_edit(&review, 1060, false).is_ok());
+
+        let rules = ReviewEditRules { rating_lock_seconds: 0, max_edits: 0, ..locked };
+        assert!(rules.check_edit(&review, i64::MAX, true).is_ok());
+
+        assert!(ReviewEditRules::default().check_edit(&review, 1000, true).is_ok());
+    }
+
+    #[test]
+    fn roll_epoch_skips_idle_epochs_on_the_original_grid() {
+        let mut state = emission_state();
+        state.epoch_emitted = 40;
+
+        state.roll_epoch(1099);
+        assert_eq!((state.epoch_index, state.epoch_started_at, state.epoch_emitted), (0, 1000, 40));
+
+        assert_eq!(state.current_epoch_index(1350), 3);
+        state.roll_epoch(1350);
+        assert_eq!((state.epoch_index, state.epoch_started_at, state.epoch_emitted), (3, 1300, 0));
+    }
+
+    #[test]
+    fn can_emit_respects_each_limit() {
+        let mut state = emission_state();
+        assert!(state.can_emit(100, 500));
+
+        // Epoch cap.
+        state.epoch_emitted = 60;
+        assert!(state.can_emit(40, 500));
+        assert!(!state.can_emit(41, 500));
+
+        // Lifetime cap.
+        state.epoch_emitted = 0;
+        state.lifetime_emitted = 950;
+        assert!(state.can_emit(50, 500));
+        assert!(!state.can_emit(51, 500));
+
+        // Tokens reserved for finalized epochs are not available.
+        state.lifetime_emitted = 0;
+        state.reserved = 470;
+        assert!(state.can_emit(30, 500));
+        assert!(!state.can_emit(31, 500));
+    }
+
+    #[test]
+    fn record_return_credits_the_epoch_only_for_current_payouts() {
+        let mut state = emission_state();
+        state.record_emission(80).unwrap();
+        state.roll_epoch(1100);
+        state.record_emission(50).unwrap();
+        assert_eq!((state.lifetime_emitted, state.epoch_emitted), (130, 50));
+
+        state.record_return(30, Some(1050));
+        assert_eq!((state.lifetime_emitted, state.epoch_emitted), (100, 50));
+        state.record_return(20, None);
+        assert_eq!((state.lifetime_emitted, state.epoch_emitted), (80, 50));
+        state.record_return(10, Some(1100));
+        assert_eq!((state.lifetime_emitted, state.epoch_emitted), (70, 40));
+    }
+
+    #[test]
+    fn release_reserved_returns_budget_without_touching_the_epoch() {
+        let mut state = emission_state();
+        state.record_emission(50).unwrap();
+        state.reserved = 200;
+        state.lifetime_emitted = 250;
+
+        state.release_reserved(70);
+        assert_eq!((state.reserved, state.lifetime_emitted, state.epoch_emitted), (130, 180, 50));
+    }
+}